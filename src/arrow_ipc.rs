@@ -0,0 +1,400 @@
+// src/arrow_ipc.rs
+
+use crate::error::PipelineError;
+use crate::input::create_file;
+use arrow::array::{
+    as_boolean_array, as_primitive_array, as_string_array, Array, ArrayRef, BooleanArray,
+    Date32Array, Float64Array, Int64Array, StringArray, TimestampMicrosecondArray,
+    TimestampMillisecondArray, TimestampNanosecondArray, UInt64Array,
+};
+use arrow::compute::{cast, concat};
+use arrow::datatypes::{
+    DataType as ArrowType, Date32Type, Field as ArrowField, Float64Type, Int64Type,
+    Schema as ArrowSchema, SchemaRef, TimeUnit as ArrowTimeUnit, TimestampMicrosecondType,
+    TimestampMillisecondType, TimestampNanosecondType, UInt64Type,
+};
+use arrow::error::ArrowError;
+use arrow::ipc::reader::{FileReader, StreamReader};
+use arrow::ipc::writer::{FileWriter, StreamWriter};
+use arrow::record_batch::RecordBatch;
+use polars::prelude::{DataFrame, DataType, Field, NamedFrom, Schema, Series, TimeUnit};
+use std::fs::File;
+use std::sync::Arc;
+
+/// The two Arrow IPC layouts: the random-access file format (also known as
+/// Feather v2) and the streaming format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IpcLayout {
+    File,
+    Stream,
+}
+
+/// Reads the schema of an IPC dataset, typed the way `read_ipc` loads it.
+pub fn read_ipc_schema(file_path: &str, layout: IpcLayout) -> Result<Schema, PipelineError> {
+    let schema = arrow_schema(open(file_path)?, layout)?;
+
+    Ok(schema
+        .fields()
+        .iter()
+        .map(|field| Field::new(field.name(), polars_dtype(field.data_type())))
+        .collect())
+}
+
+/// Loads an IPC dataset into a DataFrame, decoding only `columns` when given.
+///
+/// Arrow columns are normalized to the widest polars type of their kind
+/// (Int64, UInt64, Float64, Boolean, Date, Datetime); anything else is loaded
+/// as Utf8. Timestamps keep their unit, with seconds read as milliseconds,
+/// and drop their time zone, leaving the UTC time.
+pub fn read_ipc(
+    file_path: &str,
+    layout: IpcLayout,
+    columns: Option<Vec<String>>,
+) -> Result<DataFrame, PipelineError> {
+    let projection = resolve_projection(file_path, layout, columns)?;
+
+    let file = open(file_path)?;
+    let (schema, batches) = match layout {
+        IpcLayout::File => {
+            let reader = FileReader::try_new(file, projection.clone())?;
+            (reader.schema(), reader.collect::<Result<Vec<_>, _>>()?)
+        }
+        IpcLayout::Stream => {
+            let reader = StreamReader::try_new(file, projection.clone())?;
+            (reader.schema(), reader.collect::<Result<Vec<_>, _>>()?)
+        }
+    };
+
+    // Batches carry the projected schema, the reader reports the full one
+    let schema = match (batches.first(), &projection) {
+        (Some(batch), _) => batch.schema(),
+        (None, Some(indices)) => Arc::new(schema.project(indices)?),
+        (None, None) => schema,
+    };
+
+    let series = schema
+        .fields()
+        .iter()
+        .enumerate()
+        .map(|(index, field)| {
+            let chunks: Vec<&dyn Array> = batches
+                .iter()
+                .map(|batch| batch.column(index).as_ref())
+                .collect();
+            let array = match chunks.len() {
+                0 => arrow::array::new_empty_array(field.data_type()),
+                _ => concat(&chunks)?,
+            };
+            to_series(field.name(), &array)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(DataFrame::new(series)?)
+}
+
+/// Calls `f` with every record batch of an IPC dataset as a DataFrame, so that
+/// only one batch is held in memory at a time.
+pub fn for_each_ipc_batch<F>(
+    file_path: &str,
+    layout: IpcLayout,
+    columns: Option<Vec<String>>,
+    mut f: F,
+) -> Result<(), PipelineError>
+where
+    F: FnMut(DataFrame) -> Result<(), PipelineError>,
+{
+    let projection = resolve_projection(file_path, layout, columns)?;
+
+    let file = open(file_path)?;
+    let batches: Box<dyn Iterator<Item = Result<RecordBatch, ArrowError>>> = match layout {
+        IpcLayout::File => Box::new(FileReader::try_new(file, projection)?),
+        IpcLayout::Stream => Box::new(StreamReader::try_new(file, projection)?),
+    };
+
+    for batch in batches {
+        let batch = batch?;
+        let series = batch
+            .schema()
+            .fields()
+            .iter()
+            .zip(batch.columns())
+            .map(|(field, array)| to_series(field.name(), array))
+            .collect::<Result<Vec<_>, _>>()?;
+        f(DataFrame::new(series)?)?;
+    }
+
+    Ok(())
+}
+
+/// Writes a DataFrame as a single-batch IPC file or stream.
+pub fn write_ipc(df: &DataFrame, file_path: &str, layout: IpcLayout) -> Result<(), PipelineError> {
+    let mut fields = Vec::with_capacity(df.width());
+    let mut columns = Vec::with_capacity(df.width());
+    for series in df.get_columns() {
+        let array = to_arrow_array(series)?;
+        fields.push(ArrowField::new(
+            series.name(),
+            array.data_type().clone(),
+            true,
+        ));
+        columns.push(array);
+    }
+
+    let schema = ArrowSchema::new(fields);
+    let batch = RecordBatch::try_new(Arc::new(schema.clone()), columns)?;
+    let file = create_file(file_path)?;
+
+    match layout {
+        IpcLayout::File => {
+            let mut writer = FileWriter::try_new(file, &schema)?;
+            writer.write(&batch)?;
+            writer.finish()?;
+        }
+        IpcLayout::Stream => {
+            let mut writer = StreamWriter::try_new(file, &schema)?;
+            writer.write(&batch)?;
+            writer.finish()?;
+        }
+    }
+
+    Ok(())
+}
+
+fn open(file_path: &str) -> Result<File, PipelineError> {
+    File::open(file_path).map_err(|err| PipelineError::io_at(file_path, err))
+}
+
+// The projection is positional, so resolve names against the full schema
+fn resolve_projection(
+    file_path: &str,
+    layout: IpcLayout,
+    columns: Option<Vec<String>>,
+) -> Result<Option<Vec<usize>>, PipelineError> {
+    let columns = match columns {
+        Some(columns) => columns,
+        None => return Ok(None),
+    };
+
+    let schema = arrow_schema(open(file_path)?, layout)?;
+    let indices = columns
+        .iter()
+        .map(|column| {
+            schema
+                .index_of(column)
+                .map_err(|_| PipelineError::MissingColumn(column.clone()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Some(indices))
+}
+
+fn arrow_schema(file: File, layout: IpcLayout) -> Result<SchemaRef, PipelineError> {
+    Ok(match layout {
+        IpcLayout::File => FileReader::try_new(file, None)?.schema(),
+        IpcLayout::Stream => StreamReader::try_new(file, None)?.schema(),
+    })
+}
+
+fn polars_dtype(dtype: &ArrowType) -> DataType {
+    match dtype {
+        ArrowType::Int8 | ArrowType::Int16 | ArrowType::Int32 | ArrowType::Int64 => DataType::Int64,
+        ArrowType::UInt8 | ArrowType::UInt16 | ArrowType::UInt32 | ArrowType::UInt64 => {
+            DataType::UInt64
+        }
+        ArrowType::Float16 | ArrowType::Float32 | ArrowType::Float64 => DataType::Float64,
+        ArrowType::Boolean => DataType::Boolean,
+        ArrowType::Date32 | ArrowType::Date64 => DataType::Date,
+        ArrowType::Timestamp(unit, _) => DataType::Datetime(
+            match unit {
+                ArrowTimeUnit::Second | ArrowTimeUnit::Millisecond => TimeUnit::Milliseconds,
+                ArrowTimeUnit::Microsecond => TimeUnit::Microseconds,
+                ArrowTimeUnit::Nanosecond => TimeUnit::Nanoseconds,
+            },
+            None,
+        ),
+        _ => DataType::Utf8,
+    }
+}
+
+fn to_series(name: &str, array: &ArrayRef) -> Result<Series, PipelineError> {
+    let unsupported = |_| {
+        PipelineError::Parse(format!(
+            "column '{}' has unsupported Arrow type {}",
+            name,
+            array.data_type()
+        ))
+    };
+
+    let series = match polars_dtype(array.data_type()) {
+        DataType::Int64 => {
+            let array = cast(array, &ArrowType::Int64).map_err(unsupported)?;
+            let values: Vec<Option<i64>> = as_primitive_array::<Int64Type>(&array).iter().collect();
+            Series::new(name, values)
+        }
+        DataType::UInt64 => {
+            let array = cast(array, &ArrowType::UInt64).map_err(unsupported)?;
+            let values: Vec<Option<u64>> =
+                as_primitive_array::<UInt64Type>(&array).iter().collect();
+            Series::new(name, values)
+        }
+        DataType::Float64 => {
+            let array = cast(array, &ArrowType::Float64).map_err(unsupported)?;
+            let values: Vec<Option<f64>> =
+                as_primitive_array::<Float64Type>(&array).iter().collect();
+            Series::new(name, values)
+        }
+        DataType::Boolean => {
+            let values: Vec<Option<bool>> = as_boolean_array(array).iter().collect();
+            Series::new(name, values)
+        }
+        DataType::Date => {
+            let array = cast(array, &ArrowType::Date32).map_err(unsupported)?;
+            let days: Vec<Option<i32>> = as_primitive_array::<Date32Type>(&array).iter().collect();
+            Series::new(name, days).cast(&DataType::Date)?
+        }
+        DataType::Datetime(unit, _) => {
+            // Ticks since the epoch in the unit of the polars type
+            let ticks: Vec<Option<i64>> = match unit {
+                TimeUnit::Milliseconds => {
+                    let array = cast(
+                        array,
+                        &ArrowType::Timestamp(ArrowTimeUnit::Millisecond, None),
+                    )
+                    .map_err(unsupported)?;
+                    as_primitive_array::<TimestampMillisecondType>(&array)
+                        .iter()
+                        .collect()
+                }
+                TimeUnit::Microseconds => as_primitive_array::<TimestampMicrosecondType>(array)
+                    .iter()
+                    .collect(),
+                TimeUnit::Nanoseconds => as_primitive_array::<TimestampNanosecondType>(array)
+                    .iter()
+                    .collect(),
+            };
+            Series::new(name, ticks).cast(&DataType::Datetime(unit, None))?
+        }
+        _ => {
+            let array = cast(array, &ArrowType::Utf8).map_err(unsupported)?;
+            let values: Vec<Option<&str>> = as_string_array(&array).iter().collect();
+            Series::new(name, values)
+        }
+    };
+
+    Ok(series)
+}
+
+fn to_arrow_array(series: &Series) -> Result<ArrayRef, PipelineError> {
+    let dtype = series.dtype();
+
+    let array: ArrayRef = if dtype.is_float() {
+        let series = series.cast(&DataType::Float64)?;
+        Arc::new(series.f64()?.into_iter().collect::<Float64Array>())
+    } else if dtype.is_signed() {
+        let series = series.cast(&DataType::Int64)?;
+        Arc::new(series.i64()?.into_iter().collect::<Int64Array>())
+    } else if dtype.is_unsigned() {
+        let series = series.cast(&DataType::UInt64)?;
+        Arc::new(series.u64()?.into_iter().collect::<UInt64Array>())
+    } else if dtype == &DataType::Boolean {
+        Arc::new(series.bool()?.into_iter().collect::<BooleanArray>())
+    } else if dtype == &DataType::Date {
+        let days = series.cast(&DataType::Int32)?;
+        Arc::new(days.i32()?.into_iter().collect::<Date32Array>())
+    } else if let DataType::Datetime(unit, _) = dtype {
+        let ticks = series.cast(&DataType::Int64)?;
+        let ticks = ticks.i64()?.into_iter();
+        match unit {
+            TimeUnit::Milliseconds => Arc::new(ticks.collect::<TimestampMillisecondArray>()),
+            TimeUnit::Microseconds => Arc::new(ticks.collect::<TimestampMicrosecondArray>()),
+            TimeUnit::Nanoseconds => Arc::new(ticks.collect::<TimestampNanosecondArray>()),
+        }
+    } else {
+        let series = series.cast(&DataType::Utf8)?;
+        Arc::new(series.utf8()?.into_iter().collect::<StringArray>())
+    };
+
+    Ok(array)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Date64Array, TimestampSecondArray};
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("{}-{}", std::process::id(), name))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn dates_and_datetimes_round_trip() {
+        let dates = Series::new("day", [Some(19_000), None, Some(-1)]).cast(&DataType::Date);
+        let datetimes = Series::new("at", [Some(1_700_000_000_000_i64), Some(0), None])
+            .cast(&DataType::Datetime(TimeUnit::Milliseconds, None));
+        let df = DataFrame::new(vec![
+            Series::new("id", [1_i64, 2, 3]),
+            dates.unwrap(),
+            datetimes.unwrap(),
+        ])
+        .unwrap();
+
+        for (name, layout) in [
+            ("dates.arrow", IpcLayout::File),
+            ("dates.arrows", IpcLayout::Stream),
+        ] {
+            let file_path = temp_path(name);
+            write_ipc(&df, &file_path, layout).unwrap();
+            let schema = read_ipc_schema(&file_path, layout).unwrap();
+            let read = read_ipc(&file_path, layout, None).unwrap();
+            std::fs::remove_file(&file_path).unwrap();
+
+            assert_eq!(schema, df.schema());
+            assert!(
+                read.frame_equal_missing(&df),
+                "{} read back as {}",
+                name,
+                read
+            );
+        }
+    }
+
+    #[test]
+    fn other_arrow_date_types_are_normalized() {
+        let schema = ArrowSchema::new(vec![
+            ArrowField::new("day", ArrowType::Date64, true),
+            ArrowField::new(
+                "at",
+                ArrowType::Timestamp(ArrowTimeUnit::Second, Some("UTC".into())),
+                true,
+            ),
+        ]);
+        let at = TimestampSecondArray::from(vec![Some(86_400), None]).with_timezone("UTC");
+        let batch = RecordBatch::try_new(
+            Arc::new(schema.clone()),
+            vec![
+                Arc::new(Date64Array::from(vec![Some(86_400_000), None])),
+                Arc::new(at),
+            ],
+        )
+        .unwrap();
+        let file_path = temp_path("normalized.arrow");
+        let mut writer = FileWriter::try_new(File::create(&file_path).unwrap(), &schema).unwrap();
+        writer.write(&batch).unwrap();
+        writer.finish().unwrap();
+
+        let df = read_ipc(&file_path, IpcLayout::File, None).unwrap();
+        std::fs::remove_file(&file_path).unwrap();
+
+        let days = Series::new("day", [Some(1), None])
+            .cast(&DataType::Date)
+            .unwrap();
+        let at = Series::new("at", [Some(86_400_000_i64), None])
+            .cast(&DataType::Datetime(TimeUnit::Milliseconds, None))
+            .unwrap();
+        assert!(df.column("day").unwrap().series_equal_missing(&days));
+        assert!(df.column("at").unwrap().series_equal_missing(&at));
+    }
+}
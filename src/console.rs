@@ -0,0 +1,199 @@
+// src/console.rs
+
+use data_science_pipeline::correlation::CorrelationMatrix;
+use data_science_pipeline::data_processing::{DataSummary, Pca};
+use data_science_pipeline::profile::ColumnProfile;
+
+/// Prints one column per summary and one row per statistic.
+pub fn print_summary_table(summaries: &[DataSummary]) {
+    let widths: Vec<usize> = summaries
+        .iter()
+        .map(|summary| summary.label().len().max(16))
+        .collect();
+
+    let print_row = |label: &str, cells: Vec<String>| {
+        print!("{:<12}", label);
+        for (cell, width) in cells.iter().zip(&widths) {
+            print!(" | {:>width$}", cell, width = width);
+        }
+        println!();
+    };
+
+    print_row(
+        "Statistic",
+        summaries.iter().map(DataSummary::label).collect(),
+    );
+    print_row(
+        &"-".repeat(12),
+        widths.iter().map(|width| "-".repeat(*width)).collect(),
+    );
+    print_row(
+        "Count",
+        summaries.iter().map(|s| s.count.to_string()).collect(),
+    );
+    print_row(
+        "Null count",
+        summaries.iter().map(|s| s.null_count.to_string()).collect(),
+    );
+
+    let statistics: Vec<_> = summaries.iter().map(DataSummary::statistics).collect();
+    if let Some(first) = statistics.first() {
+        for (index, (label, _)) in first.iter().enumerate() {
+            print_row(
+                label,
+                statistics
+                    .iter()
+                    .map(|row| format!("{:.4}", row[index].1))
+                    .collect(),
+            );
+        }
+    }
+}
+
+/// Prints the coefficient, p-value and observation count of every pair.
+pub fn print_correlations(matrix: &CorrelationMatrix) {
+    println!();
+    println!("{} correlation", matrix.method.name());
+    for i in 0..matrix.columns.len() {
+        for j in i + 1..matrix.columns.len() {
+            println!(
+                "{} ~ {}: r = {:.4}, p = {:.4e}, n = {}",
+                matrix.columns[i],
+                matrix.columns[j],
+                matrix.coefficients[i][j],
+                matrix.p_values[i][j],
+                matrix.observations[i][j]
+            );
+        }
+    }
+    println!();
+}
+
+/// Prints the explained variance and loadings of every component.
+pub fn print_pca(pca: &Pca) {
+    println!("Principal components");
+    let mut cumulative = 0.0;
+    for (index, (eigenvalue, ratio)) in pca
+        .eigenvalues
+        .iter()
+        .zip(&pca.explained_variance_ratio)
+        .enumerate()
+    {
+        cumulative += ratio;
+        println!(
+            "PC{}: eigenvalue = {:.4}, explained = {:.2}%, cumulative = {:.2}%",
+            index + 1,
+            eigenvalue,
+            ratio * 100.0,
+            cumulative * 100.0
+        );
+    }
+    println!();
+
+    let components: Vec<String> = (1..=pca.loadings.len())
+        .map(|index| format!("PC{}", index))
+        .collect();
+    print_matrix("Loadings", &components, &pca.columns, &pca.loadings);
+
+    if let Some(scores) = &pca.scores {
+        println!(
+            "Projected {} rows onto {} components.",
+            scores.height(),
+            scores.width()
+        );
+        println!();
+    }
+}
+
+/// Prints a labeled matrix with four decimals.
+pub fn print_matrix(title: &str, rows: &[String], columns: &[String], values: &[Vec<f64>]) {
+    let label_width = rows.iter().map(String::len).max().unwrap_or(0).max(12);
+    let widths: Vec<usize> = columns.iter().map(|column| column.len().max(12)).collect();
+
+    println!("{}", title);
+    print!("{:<label_width$}", "", label_width = label_width);
+    for (column, width) in columns.iter().zip(&widths) {
+        print!(" | {:>width$}", column, width = width);
+    }
+    println!();
+    for (row, row_values) in rows.iter().zip(values) {
+        print!("{:<label_width$}", row, label_width = label_width);
+        for (value, width) in row_values.iter().zip(&widths) {
+            print!(" | {:>width$.4}", value, width = width);
+        }
+        println!();
+    }
+    println!();
+}
+
+/// Prints the type, nulls, cardinality and text lengths of every column, then
+/// their most frequent values and the statistics of the numeric ones.
+pub fn print_profile(profiles: &[ColumnProfile]) {
+    let name_width = profiles
+        .iter()
+        .map(|profile| profile.column.len())
+        .max()
+        .unwrap_or(0)
+        .max(6);
+    println!(
+        "{:<name_width$} | {:<12} | {:>10} | {:>7} | {:>10} | {:>11}",
+        "Column",
+        "Type",
+        "Nulls",
+        "Null %",
+        "Distinct",
+        "Length",
+        name_width = name_width
+    );
+    println!(
+        "{}-|-{}-|-{}-|-{}-|-{}-|-{}",
+        "-".repeat(name_width),
+        "-".repeat(12),
+        "-".repeat(10),
+        "-".repeat(7),
+        "-".repeat(10),
+        "-".repeat(11)
+    );
+    for profile in profiles {
+        let lengths = match profile.lengths {
+            Some((min, max)) => format!("{}-{}", min, max),
+            None => String::new(),
+        };
+        println!(
+            "{:<name_width$} | {:<12} | {:>10} | {:>6.2}% | {:>10} | {:>11}",
+            profile.column,
+            profile.dtype,
+            profile.null_count,
+            profile.null_percent,
+            profile.distinct_count,
+            lengths,
+            name_width = name_width
+        );
+    }
+    println!();
+
+    println!("Most frequent values");
+    for profile in profiles {
+        let values: Vec<String> = profile
+            .top_values
+            .iter()
+            .map(|(value, count)| format!("{} ({})", value, count))
+            .collect();
+        println!(
+            "{:<name_width$} | {}",
+            profile.column,
+            values.join(", "),
+            name_width = name_width
+        );
+    }
+    println!();
+
+    let summaries: Vec<DataSummary> = profiles
+        .iter()
+        .filter_map(|profile| profile.summary.clone())
+        .collect();
+    if !summaries.is_empty() {
+        print_summary_table(&summaries);
+        println!();
+    }
+}
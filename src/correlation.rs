@@ -0,0 +1,480 @@
+// src/correlation.rs
+
+use crate::error::PipelineError;
+use clap::ValueEnum;
+use polars::prelude::*;
+
+/// Correlation coefficients the pipeline can compute.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum CorrelationMethod {
+    Pearson,
+    /// Pearson correlation of the ranks
+    Spearman,
+    /// Kendall's tau-b, which accounts for ties
+    Kendall,
+}
+
+impl CorrelationMethod {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CorrelationMethod::Pearson => "Pearson",
+            CorrelationMethod::Spearman => "Spearman",
+            CorrelationMethod::Kendall => "Kendall",
+        }
+    }
+}
+
+/// Pairwise correlations of a set of columns. Entry `[i][j]` relates
+/// `columns[i]` to `columns[j]` over the rows where both are non-null.
+#[derive(Clone, Debug)]
+pub struct CorrelationMatrix {
+    pub method: CorrelationMethod,
+    pub columns: Vec<String>,
+    pub coefficients: Vec<Vec<f64>>,
+    /// Two-sided p-values against the hypothesis of no correlation
+    pub p_values: Vec<Vec<f64>>,
+    pub observations: Vec<Vec<usize>>,
+}
+
+/// Correlates every pair of `columns` in `frame` with the given method.
+///
+/// Pearson and Spearman p-values come from Student's t distribution with
+/// n - 2 degrees of freedom, Kendall's from the tie-corrected normal
+/// approximation. Coefficients and p-values are NaN when a column is constant
+/// or a pair has too few rows.
+pub fn correlation_matrix(
+    frame: &DataFrame,
+    columns: &[String],
+    method: CorrelationMethod,
+) -> Result<CorrelationMatrix, PipelineError> {
+    if columns.len() < 2 {
+        return Err(PipelineError::EmptyData(
+            "correlations need at least two columns".to_string(),
+        ));
+    }
+
+    let values = columns
+        .iter()
+        .map(|column| {
+            let series = frame.column(column)?;
+            if !series.dtype().is_numeric() {
+                return Err(PipelineError::TypeMismatch {
+                    column: column.clone(),
+                    dtype: series.dtype().to_string(),
+                });
+            }
+            let series = series.cast(&DataType::Float64)?;
+            let values: Vec<Option<f64>> = series.f64()?.into_iter().collect();
+            Ok(values)
+        })
+        .collect::<Result<Vec<_>, PipelineError>>()?;
+
+    let size = columns.len();
+    let mut coefficients = vec![vec![1.0; size]; size];
+    let mut p_values = vec![vec![0.0; size]; size];
+    let mut observations = vec![vec![0; size]; size];
+
+    for i in 0..size {
+        observations[i][i] = values[i].iter().flatten().count();
+
+        for j in i + 1..size {
+            // Pairwise deletion: keep the rows where both values are present
+            let (x, y): (Vec<f64>, Vec<f64>) = values[i]
+                .iter()
+                .zip(&values[j])
+                .filter_map(|(x, y)| Some(((*x)?, (*y)?)))
+                .unzip();
+
+            let (coefficient, p_value) = match method {
+                CorrelationMethod::Pearson => pearson(&x, &y),
+                CorrelationMethod::Spearman => pearson(&ranks(&x), &ranks(&y)),
+                CorrelationMethod::Kendall => kendall_tau_b(&x, &y),
+            };
+
+            coefficients[i][j] = coefficient;
+            coefficients[j][i] = coefficient;
+            p_values[i][j] = p_value;
+            p_values[j][i] = p_value;
+            observations[i][j] = x.len();
+            observations[j][i] = x.len();
+        }
+    }
+
+    Ok(CorrelationMatrix {
+        method,
+        columns: columns.to_vec(),
+        coefficients,
+        p_values,
+        observations,
+    })
+}
+
+// Pearson's r and its two-sided p-value
+fn pearson(x: &[f64], y: &[f64]) -> (f64, f64) {
+    let n = x.len();
+    if n < 2 {
+        return (f64::NAN, f64::NAN);
+    }
+
+    let mean_x = x.iter().sum::<f64>() / n as f64;
+    let mean_y = y.iter().sum::<f64>() / n as f64;
+    let (mut sxy, mut sxx, mut syy) = (0.0, 0.0, 0.0);
+    for (x, y) in x.iter().zip(y) {
+        let (dx, dy) = (x - mean_x, y - mean_y);
+        sxy += dx * dy;
+        sxx += dx * dx;
+        syy += dy * dy;
+    }
+    let r = (sxy / (sxx * syy).sqrt()).clamp(-1.0, 1.0);
+
+    if n < 3 || r.is_nan() {
+        return (r, f64::NAN);
+    }
+    if r.abs() == 1.0 {
+        return (r, 0.0);
+    }
+
+    // P(|T| > t) for T ~ t(df) is I_{df / (df + t²)}(df / 2, 1 / 2)
+    let df = (n - 2) as f64;
+    let t_squared = r * r * df / (1.0 - r * r);
+    (r, incomplete_beta(df / 2.0, 0.5, df / (df + t_squared)))
+}
+
+// 1-based ranks, with tied values sharing the average of their ranks
+fn ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+
+    let mut ranks = vec![0.0; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && values[order[end]] == values[order[start]] {
+            end += 1;
+        }
+        let rank = (start + end + 1) as f64 / 2.0;
+        for &index in &order[start..end] {
+            ranks[index] = rank;
+        }
+        start = end;
+    }
+
+    ranks
+}
+
+/// Kendall's tau-b and its two-sided p-value, in O(n log n) with Knight's
+/// algorithm: after sorting the pairs by x, the discordant pairs are the
+/// swaps a merge sort by y performs.
+fn kendall_tau_b(x: &[f64], y: &[f64]) -> (f64, f64) {
+    let n = x.len();
+    if n < 2 {
+        return (f64::NAN, f64::NAN);
+    }
+
+    let mut pairs: Vec<(f64, f64)> = x.iter().copied().zip(y.iter().copied()).collect();
+    pairs.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    let x_ties = tie_sizes(&pairs, |a, b| a.0 == b.0);
+    let joint_ties = tie_sizes(&pairs, |a, b| a == b);
+
+    let mut sorted_y: Vec<f64> = pairs.iter().map(|pair| pair.1).collect();
+    let discordant = merge_sort_swaps(&mut sorted_y);
+    let y_ties = tie_sizes(&sorted_y, |a, b| a == b);
+
+    let pair_count = |t: &f64| t * (t - 1.0) / 2.0;
+    let nf = n as f64;
+    let n0 = nf * (nf - 1.0) / 2.0;
+    let n1: f64 = x_ties.iter().map(pair_count).sum();
+    let n2: f64 = y_ties.iter().map(pair_count).sum();
+    let n3: f64 = joint_ties.iter().map(pair_count).sum();
+
+    // Concordant minus discordant pairs
+    let s = n0 - n1 - n2 + n3 - 2.0 * discordant;
+    let tau = s / ((n0 - n1) * (n0 - n2)).sqrt();
+
+    if n < 3 || tau.is_nan() {
+        return (tau, f64::NAN);
+    }
+
+    // Variance of s under independence, corrected for ties
+    let sum = |ties: &[f64], f: fn(f64) -> f64| ties.iter().map(|t| f(*t)).sum::<f64>();
+    let (x_v, y_v) = (
+        sum(&x_ties, |t| t * (t - 1.0) * (2.0 * t + 5.0)),
+        sum(&y_ties, |t| t * (t - 1.0) * (2.0 * t + 5.0)),
+    );
+    let (x_1, y_1) = (
+        sum(&x_ties, |t| t * (t - 1.0)),
+        sum(&y_ties, |t| t * (t - 1.0)),
+    );
+    let (x_2, y_2) = (
+        sum(&x_ties, |t| t * (t - 1.0) * (t - 2.0)),
+        sum(&y_ties, |t| t * (t - 1.0) * (t - 2.0)),
+    );
+    let variance = (nf * (nf - 1.0) * (2.0 * nf + 5.0) - x_v - y_v) / 18.0
+        + x_1 * y_1 / (2.0 * nf * (nf - 1.0))
+        + x_2 * y_2 / (9.0 * nf * (nf - 1.0) * (nf - 2.0));
+
+    let z = s / variance.sqrt();
+    (tau, erfc(z.abs() / std::f64::consts::SQRT_2))
+}
+
+// Sizes of the runs of equal neighbours longer than one
+fn tie_sizes<T>(sorted: &[T], same: impl Fn(&T, &T) -> bool) -> Vec<f64> {
+    let mut sizes = Vec::new();
+    let mut run = 1;
+    for window in sorted.windows(2) {
+        if same(&window[0], &window[1]) {
+            run += 1;
+        } else {
+            if run > 1 {
+                sizes.push(run as f64);
+            }
+            run = 1;
+        }
+    }
+    if run > 1 {
+        sizes.push(run as f64);
+    }
+    sizes
+}
+
+// Sorts `values` and returns how many inversions the sort removed; equal
+// values are never counted as inverted
+fn merge_sort_swaps(values: &mut [f64]) -> f64 {
+    let n = values.len();
+    if n < 2 {
+        return 0.0;
+    }
+
+    let mid = n / 2;
+    let mut swaps = merge_sort_swaps(&mut values[..mid]) + merge_sort_swaps(&mut values[mid..]);
+
+    let mut merged = Vec::with_capacity(n);
+    let (mut i, mut j) = (0, mid);
+    while i < mid && j < n {
+        if values[j] < values[i] {
+            merged.push(values[j]);
+            swaps += (mid - i) as f64;
+            j += 1;
+        } else {
+            merged.push(values[i]);
+            i += 1;
+        }
+    }
+    merged.extend_from_slice(&values[i..mid]);
+    merged.extend_from_slice(&values[j..]);
+    values.copy_from_slice(&merged);
+
+    swaps
+}
+
+/// Regularized incomplete beta function I_x(a, b), evaluated with the
+/// continued fraction from Numerical Recipes.
+fn incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+
+    let front =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+
+    // The continued fraction converges quickly only on this side
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_continued_fraction(a, b, x) / a
+    } else {
+        1.0 - front * beta_continued_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+// Modified Lentz evaluation of the incomplete beta continued fraction
+fn beta_continued_fraction(a: f64, b: f64, x: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+
+    let mut c = 1.0;
+    let mut d = 1.0 - (a + b) * x / (a + 1.0);
+    if d.abs() < TINY {
+        d = TINY;
+    }
+    d = 1.0 / d;
+    let mut result = d;
+
+    for m in 1..=300 {
+        let m = m as f64;
+
+        // Even step
+        let numerator = m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m));
+        d = 1.0 + numerator * d;
+        d = if d.abs() < TINY { TINY } else { d };
+        c = 1.0 + numerator / c;
+        c = if c.abs() < TINY { TINY } else { c };
+        d = 1.0 / d;
+        result *= d * c;
+
+        // Odd step
+        let numerator = -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0));
+        d = 1.0 + numerator * d;
+        d = if d.abs() < TINY { TINY } else { d };
+        c = 1.0 + numerator / c;
+        c = if c.abs() < TINY { TINY } else { c };
+        d = 1.0 / d;
+        let delta = d * c;
+        result *= delta;
+
+        if (delta - 1.0).abs() < 1e-15 {
+            break;
+        }
+    }
+
+    result
+}
+
+// Lanczos approximation (g = 7, n = 9) of ln Γ(x) for x >= 0.5
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+
+    let x = x - 1.0;
+    let series = COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFICIENTS[0], |sum, (i, c)| {
+            sum + c / (x + i as f64 + 1.0)
+        });
+    let t = x + 7.5;
+
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + series.ln()
+}
+
+// Complementary error function, with a fractional error below 1.2e-7
+// everywhere (Chebyshev fit from Numerical Recipes)
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let polynomial = [
+        -1.265_512_23,
+        1.000_023_68,
+        0.374_091_96,
+        0.096_784_18,
+        -0.186_288_06,
+        0.278_868_07,
+        -1.135_203_98,
+        1.488_515_87,
+        -0.822_152_23,
+        0.170_872_77,
+    ]
+    .iter()
+    .rev()
+    .fold(0.0, |acc, c| c + t * acc);
+    let result = t * (-z * z + polynomial).exp();
+
+    if x >= 0.0 {
+        result
+    } else {
+        2.0 - result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() < tolerance,
+            "{} is not within {} of {}",
+            actual,
+            tolerance,
+            expected
+        );
+    }
+
+    #[test]
+    fn pearson_matches_the_t_distribution() {
+        // r = sqrt(0.6), t = 2.1213 with 3 degrees of freedom
+        let (r, p) = pearson(&[1.0, 2.0, 3.0, 4.0, 5.0], &[2.0, 4.0, 5.0, 4.0, 5.0]);
+        assert_close(r, 0.6f64.sqrt(), 1e-12);
+        assert_close(p, 0.124_027_062_657_554_6, 1e-9);
+    }
+
+    #[test]
+    fn ranks_share_the_average_of_ties() {
+        assert_eq!(
+            ranks(&[10.0, 20.0, 10.0, 30.0, 20.0, 20.0]),
+            vec![1.5, 4.0, 1.5, 6.0, 4.0, 4.0]
+        );
+    }
+
+    #[test]
+    fn kendall_tau_b_corrects_for_ties() {
+        // 22 concordant and 2 discordant pairs out of 28, with two tied
+        // pairs in x and two in y: tau-b = 20 / sqrt(26 * 26)
+        let x = [1.0, 2.0, 2.0, 3.0, 4.0, 4.0, 5.0, 6.0];
+        let y = [2.0, 1.0, 3.0, 3.0, 5.0, 4.0, 4.0, 7.0];
+        let (tau, p) = kendall_tau_b(&x, &y);
+        assert_close(tau, 10.0 / 13.0, 1e-12);
+        assert_close(p, 0.010_747_577_580_460_086, 1e-6);
+    }
+
+    #[test]
+    fn kendall_tau_b_of_reversed_order_is_minus_one() {
+        let (tau, _) = kendall_tau_b(&[1.0, 2.0, 3.0, 4.0], &[8.0, 6.0, 4.0, 2.0]);
+        assert_close(tau, -1.0, 1e-12);
+    }
+
+    #[test]
+    fn incomplete_beta_matches_closed_forms() {
+        assert_close(incomplete_beta(1.0, 1.0, 0.3), 0.3, 1e-12);
+        assert_close(incomplete_beta(3.0, 1.0, 0.4), 0.4f64.powi(3), 1e-12);
+        assert_close(incomplete_beta(1.0, 4.0, 0.2), 1.0 - 0.8f64.powi(4), 1e-12);
+        assert_close(incomplete_beta(2.5, 2.5, 0.5), 0.5, 1e-12);
+        // I_x(1/2, 1/2) = 2 / pi * asin(sqrt(x))
+        assert_close(
+            incomplete_beta(0.5, 0.5, 0.3),
+            0.369_010_119_565_545_4,
+            1e-10,
+        );
+        // Two-sided 5% critical value of Student's t with 10 degrees of freedom
+        let t: f64 = 2.228_138_851_986_52;
+        assert_close(incomplete_beta(5.0, 0.5, 10.0 / (10.0 + t * t)), 0.05, 1e-9);
+    }
+
+    #[test]
+    fn ln_gamma_and_erfc_match_reference_values() {
+        assert_close(ln_gamma(0.5), 0.572_364_942_924_700_4, 1e-12);
+        assert_close(ln_gamma(3.0), 2f64.ln(), 1e-12);
+        assert_close(ln_gamma(10.5), 13.940_625_219_403_763, 1e-10);
+        assert_close(erfc(1.0), 0.157_299_207_050_285_13, 1e-7);
+        assert_close(erfc(-1.0), 2.0 - 0.157_299_207_050_285_13, 1e-7);
+        assert_close(
+            erfc(1.959_963_984_540_054 / std::f64::consts::SQRT_2),
+            0.05,
+            1e-7,
+        );
+    }
+
+    #[test]
+    fn text_columns_are_rejected() {
+        let frame = df!(
+            "value" => [1.0, 2.0, 3.0],
+            "name" => ["a", "b", "c"]
+        )
+        .unwrap();
+        let columns = ["value".to_string(), "name".to_string()];
+        let result = correlation_matrix(&frame, &columns, CorrelationMethod::Pearson);
+        assert!(matches!(
+            result,
+            Err(PipelineError::TypeMismatch { column, .. }) if column == "name"
+        ));
+    }
+}
@@ -1,39 +1,825 @@
 // src/data_processing.rs
 
+use crate::derive::{derive_columns, input_columns, DerivedColumn};
+use crate::error::PipelineError;
+use crate::filter::Filter;
+use crate::input::{load_frame, InputFormat};
 use polars::prelude::*;
+use serde::{Deserialize, Deserializer, Serialize};
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DataSummary {
+    pub column: String,
+    pub group: Option<GroupKey>,
+    pub count: usize,
+    pub null_count: usize,
+    #[serde(deserialize_with = "nan_if_null")]
+    pub min: f64,
+    #[serde(deserialize_with = "nan_if_null")]
+    pub max: f64,
+    #[serde(deserialize_with = "nan_if_null")]
     pub mean: f64,
+    #[serde(deserialize_with = "nan_if_null")]
+    pub median: f64,
+    #[serde(deserialize_with = "nan_if_null")]
+    pub std_dev: f64,
+    #[serde(deserialize_with = "nan_if_null")]
     pub variance: f64,
+    #[serde(deserialize_with = "nan_if_null")]
+    pub q1: f64,
+    #[serde(deserialize_with = "nan_if_null")]
+    pub q3: f64,
+    #[serde(deserialize_with = "nan_if_null")]
+    pub iqr: f64,
+    #[serde(deserialize_with = "nan_if_null")]
+    pub skewness: f64,
+    #[serde(deserialize_with = "nan_if_null")]
+    pub kurtosis: f64,
 }
 
-pub fn process_data(file_path: &str, column_name: &str) -> DataSummary {
-    // Read the CSV file into a DataFrame
-    let df = CsvReader::from_path(file_path)
-        .expect("Could not read CSV file")
-        .infer_schema(None)
-        .has_header(true)
-        .finish()
-        .expect("Failed to create DataFrame");
+// JSON has no NaN and serde_json writes it as null, so read null back as NaN
+fn nan_if_null<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    Ok(Option::<f64>::deserialize(deserializer)?.unwrap_or(f64::NAN))
+}
+
+/// The group a summary was computed for: the grouping column and its value,
+/// with null keys shown as "null".
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GroupKey {
+    pub column: String,
+    pub value: String,
+}
+
+impl DataSummary {
+    /// Column name, followed by the group value for grouped summaries.
+    pub fn label(&self) -> String {
+        match &self.group {
+            Some(group) => format!("{} [{}]", self.column, group.value),
+            None => self.column.clone(),
+        }
+    }
+
+    /// Floating point statistics in display order, paired with their labels.
+    pub fn statistics(&self) -> [(&'static str, f64); 11] {
+        [
+            ("Min", self.min),
+            ("Q1", self.q1),
+            ("Median", self.median),
+            ("Q3", self.q3),
+            ("Max", self.max),
+            ("IQR", self.iqr),
+            ("Mean", self.mean),
+            ("Std dev", self.std_dev),
+            ("Variance", self.variance),
+            ("Skewness", self.skewness),
+            ("Kurtosis", self.kurtosis),
+        ]
+    }
+}
+
+/// Which columns of the input to summarize.
+#[derive(Clone, Debug)]
+pub enum ColumnSelection {
+    Named(Vec<String>),
+    AllNumeric,
+}
+
+/// The analyzed columns of the input together with their summaries.
+#[derive(Clone, Debug)]
+pub struct Analysis {
+    pub frame: DataFrame,
+    pub summaries: Vec<DataSummary>,
+}
+
+/// Reads the input, adds the `derived` columns and summarizes the selected
+/// columns over the rows matching `filter`, once per value of `group_by` when
+/// given.
+pub fn process_data(
+    file_path: &str,
+    format: InputFormat,
+    selection: &ColumnSelection,
+    group_by: Option<&str>,
+    derived: &[DerivedColumn],
+    filter: Option<&Filter>,
+) -> Result<Analysis, PipelineError> {
+    // Only parse the requested columns when they are known up front, along
+    // with those the derived columns and the filter read
+    let projection = match selection {
+        ColumnSelection::Named(columns) => {
+            let mut columns = with_group_column(columns, group_by);
+            columns.extend(filter.map(Filter::columns).unwrap_or_default());
+            Some(input_columns(&columns, derived))
+        }
+        ColumnSelection::AllNumeric => None,
+    };
+
+    // Read the input into a DataFrame once for all columns
+    let df = load_frame(file_path, format, projection)?;
+    analyze_frame(df, selection, group_by, derived, filter)
+}
+
+/// Adds the derived columns to an already loaded frame, keeps the rows
+/// matching `filter` and summarizes the selected columns.
+pub fn analyze_frame(
+    df: DataFrame,
+    selection: &ColumnSelection,
+    group_by: Option<&str>,
+    derived: &[DerivedColumn],
+    filter: Option<&Filter>,
+) -> Result<Analysis, PipelineError> {
+    let mut df = derive_columns(df, derived)?;
+    if let Some(filter) = filter {
+        df = filter.apply(df)?;
+    }
+
+    summarize_frame(&df, selection, group_by)
+}
+
+/// Summarizes the selected columns of a frame already in memory, once per
+/// value of `group_by` when given.
+///
+/// Grouped summaries are ordered by column, then by group key. The returned
+/// frame keeps the grouping column next to the analyzed ones.
+pub fn summarize_frame(
+    df: &DataFrame,
+    selection: &ColumnSelection,
+    group_by: Option<&str>,
+) -> Result<Analysis, PipelineError> {
+    if let Some(group_column) = group_by {
+        df.column(group_column)
+            .map_err(|_| PipelineError::MissingColumn(group_column.to_string()))?;
+    }
+
+    let column_names: Vec<String> = match selection {
+        ColumnSelection::Named(columns) => columns.clone(),
+        ColumnSelection::AllNumeric => df
+            .get_columns()
+            .iter()
+            .filter(|series| series.dtype().is_numeric() && Some(series.name()) != group_by)
+            .map(|series| series.name().to_string())
+            .collect(),
+    };
+
+    if column_names.is_empty() {
+        return Err(PipelineError::EmptyData(
+            "there are no numeric columns".to_string(),
+        ));
+    }
+
+    let summaries = match group_by {
+        Some(group_column) => summarize_groups(df, &column_names, group_column)?,
+        None => column_names
+            .iter()
+            .map(|column_name| summarize_column(df, column_name))
+            .collect::<Result<Vec<_>, _>>()?,
+    };
+
+    Ok(Analysis {
+        frame: df.select(with_group_column(&column_names, group_by))?,
+        summaries,
+    })
+}
+
+fn with_group_column(columns: &[String], group_by: Option<&str>) -> Vec<String> {
+    let mut columns = columns.to_vec();
+    if let Some(group_column) = group_by {
+        if !columns.iter().any(|column| column == group_column) {
+            columns.push(group_column.to_string());
+        }
+    }
+    columns
+}
+
+/// Collects the values of every column per group with a polars groupby, then
+/// summarizes each group's values like a whole column.
+fn summarize_groups(
+    df: &DataFrame,
+    column_names: &[String],
+    group_column: &str,
+) -> Result<Vec<DataSummary>, PipelineError> {
+    for column_name in column_names {
+        let dtype = df
+            .column(column_name)
+            .map_err(|_| PipelineError::MissingColumn(column_name.clone()))?
+            .dtype();
+        if !dtype.is_numeric() {
+            return Err(PipelineError::TypeMismatch {
+                column: column_name.clone(),
+                dtype: dtype.to_string(),
+            });
+        }
+    }
 
+    // Every column aggregates to a list of its values per group key. The key
+    // gets its own name so that the grouping column can be analyzed as well.
+    const KEY: &str = "__group_key";
+    let grouped = df
+        .clone()
+        .lazy()
+        .groupby([col(group_column)
+            .cast(DataType::Utf8)
+            .fill_null(lit("null"))
+            .alias(KEY)])
+        .agg(
+            column_names
+                .iter()
+                .map(|column_name| col(column_name).cast(DataType::Float64))
+                .collect::<Vec<_>>(),
+        )
+        .sort(KEY, SortOptions::default())
+        .collect()?;
+
+    let keys: Vec<String> = grouped
+        .column(KEY)?
+        .utf8()?
+        .into_iter()
+        .map(|key| key.unwrap_or("null").to_string())
+        .collect();
+
+    let mut summaries = Vec::with_capacity(column_names.len() * keys.len());
+    for column_name in column_names {
+        let lists = grouped.column(column_name)?.list()?;
+        for (key, group_values) in keys.iter().zip(lists) {
+            let group_values =
+                group_values.unwrap_or_else(|| Series::new_empty("", &DataType::Float64));
+            let null_count = group_values.null_count();
+            let mut values: Vec<f64> = group_values.f64()?.into_iter().flatten().collect();
+
+            if values.is_empty() {
+                return Err(PipelineError::EmptyData(format!(
+                    "column '{}' has no non-null values in group '{}'",
+                    column_name, key
+                )));
+            }
+
+            let mut summary = summarize_values(column_name, &mut values, null_count);
+            summary.group = Some(GroupKey {
+                column: group_column.to_string(),
+                value: key.clone(),
+            });
+            summaries.push(summary);
+        }
+    }
+
+    Ok(summaries)
+}
+
+pub(crate) fn summarize_column(
+    df: &DataFrame,
+    column_name: &str,
+) -> Result<DataSummary, PipelineError> {
     // Select the specified column for analysis
     let series = df
         .column(column_name)
-        .expect(&format!("Column '{}' not found", column_name));
+        .map_err(|_| PipelineError::MissingColumn(column_name.to_string()))?;
 
     // Ensure the column is of a numeric type
-    let series = series
-        .cast(&DataType::Float64)
-        .expect("Failed to cast column to Float64");
+    if !series.dtype().is_numeric() {
+        return Err(PipelineError::TypeMismatch {
+            column: column_name.to_string(),
+            dtype: series.dtype().to_string(),
+        });
+    }
+    let series = series.cast(&DataType::Float64)?;
+
+    // Nulls are counted but excluded from every other statistic
+    let null_count = series.null_count();
+    let mut values: Vec<f64> = series.f64()?.into_iter().flatten().collect();
+
+    if values.is_empty() {
+        return Err(PipelineError::EmptyData(format!(
+            "column '{}' has no non-null values",
+            column_name
+        )));
+    }
+
+    Ok(summarize_values(column_name, &mut values, null_count))
+}
+
+/// Computes every statistic from the non-null values of a column.
+///
+/// Moments are accumulated in a single pass and the order statistics are read
+/// from one sort of `values`, which is left sorted afterwards.
+fn summarize_values(column_name: &str, values: &mut [f64], null_count: usize) -> DataSummary {
+    let mut moments = Moments::default();
+    for &value in values.iter() {
+        moments.push(value);
+    }
+
+    values.sort_by(|a, b| a.total_cmp(b));
+    let q1 = quantile(values, 0.25);
+    let q3 = quantile(values, 0.75);
+    let variance = moments.variance();
+
+    DataSummary {
+        column: column_name.to_string(),
+        group: None,
+        count: values.len(),
+        null_count,
+        min: values[0],
+        max: values[values.len() - 1],
+        mean: moments.mean(),
+        median: quantile(values, 0.5),
+        std_dev: variance.sqrt(),
+        variance,
+        q1,
+        q3,
+        iqr: q3 - q1,
+        skewness: moments.skewness(),
+        kurtosis: moments.kurtosis(),
+    }
+}
+
+/// Principal components of a set of columns, from the eigendecomposition of
+/// their covariance matrix.
+#[derive(Clone, Debug)]
+pub struct Pca {
+    pub columns: Vec<String>,
+    /// Variance along each component, largest first
+    pub eigenvalues: Vec<f64>,
+    pub explained_variance_ratio: Vec<f64>,
+    /// One row per component with a weight per column, signed so that the
+    /// largest weight is positive
+    pub loadings: Vec<Vec<f64>>,
+    /// The rows projected onto the first components (`PC1`, `PC2`, ...) when
+    /// a projection was requested
+    pub scores: Option<DataFrame>,
+}
+
+/// Sample covariance matrix (ddof=1) of `columns`, over the rows where none
+/// of them is null.
+pub fn covariance_matrix(
+    frame: &DataFrame,
+    columns: &[String],
+) -> Result<Vec<Vec<f64>>, PipelineError> {
+    let values = complete_rows(frame, columns)?;
+    Ok(covariance(&centered(values)))
+}
+
+/// Runs a PCA over `columns`, projecting the rows onto the first `projection`
+/// components when given. Rows with a null in any column are left out.
+pub fn principal_components(
+    frame: &DataFrame,
+    columns: &[String],
+    projection: Option<usize>,
+) -> Result<Pca, PipelineError> {
+    let values = centered(complete_rows(frame, columns)?);
+    let covariance = covariance(&values);
+
+    let (eigenvalues, eigenvectors) = symmetric_eigen(&covariance);
+    let mut components: Vec<(f64, Vec<f64>)> = eigenvalues.into_iter().zip(eigenvectors).collect();
+    components.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let total: f64 = components.iter().map(|(eigenvalue, _)| eigenvalue).sum();
+    let eigenvalues: Vec<f64> = components
+        .iter()
+        .map(|(eigenvalue, _)| *eigenvalue)
+        .collect();
+    let explained_variance_ratio = eigenvalues
+        .iter()
+        .map(|eigenvalue| eigenvalue / total)
+        .collect();
+
+    // Eigenvectors are only defined up to their sign
+    let loadings: Vec<Vec<f64>> = components
+        .into_iter()
+        .map(|(_, mut vector)| {
+            let largest = vector.iter().copied().fold(0.0, |largest: f64, weight| {
+                if weight.abs() > largest.abs() {
+                    weight
+                } else {
+                    largest
+                }
+            });
+            if largest < 0.0 {
+                vector.iter_mut().for_each(|weight| *weight = -*weight);
+            }
+            vector
+        })
+        .collect();
+
+    let scores = match projection {
+        Some(count) => {
+            let rows = values[0].len();
+            let series = loadings
+                .iter()
+                .take(count)
+                .enumerate()
+                .map(|(index, loading)| {
+                    let projected: Vec<f64> = (0..rows)
+                        .map(|row| {
+                            loading
+                                .iter()
+                                .zip(&values)
+                                .map(|(weight, column)| weight * column[row])
+                                .sum()
+                        })
+                        .collect();
+                    Series::new(&format!("PC{}", index + 1), projected)
+                })
+                .collect();
+            Some(DataFrame::new(series)?)
+        }
+        None => None,
+    };
 
-    // Compute mean
-    let mean = series.mean().expect("Failed to compute mean");
+    Ok(Pca {
+        columns: columns.to_vec(),
+        eigenvalues,
+        explained_variance_ratio,
+        loadings,
+        scores,
+    })
+}
+
+// Values of `columns`, one vector per column, keeping only the rows where
+// every column is non-null
+fn complete_rows(frame: &DataFrame, columns: &[String]) -> Result<Vec<Vec<f64>>, PipelineError> {
+    let mut series = Vec::with_capacity(columns.len());
+    for column_name in columns {
+        let column = frame
+            .column(column_name)
+            .map_err(|_| PipelineError::MissingColumn(column_name.clone()))?;
+        if !column.dtype().is_numeric() {
+            return Err(PipelineError::TypeMismatch {
+                column: column_name.clone(),
+                dtype: column.dtype().to_string(),
+            });
+        }
+        series.push(column.cast(&DataType::Float64)?);
+    }
+
+    let chunks = series
+        .iter()
+        .map(|series| series.f64())
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut values = vec![Vec::with_capacity(frame.height()); columns.len()];
+    let mut iterators: Vec<_> = chunks.iter().map(|chunk| chunk.into_iter()).collect();
+    'rows: for _ in 0..frame.height() {
+        let row: Vec<Option<f64>> = iterators
+            .iter_mut()
+            .map(|iterator| iterator.next().flatten())
+            .collect();
+        let mut complete = Vec::with_capacity(row.len());
+        for value in row {
+            match value {
+                Some(value) => complete.push(value),
+                None => continue 'rows,
+            }
+        }
+        for (column, value) in values.iter_mut().zip(complete) {
+            column.push(value);
+        }
+    }
+
+    if values.first().map_or(0, Vec::len) < 2 {
+        return Err(PipelineError::EmptyData(
+            "a covariance matrix needs at least two rows without nulls".to_string(),
+        ));
+    }
+
+    Ok(values)
+}
+
+// Subtracts the mean of every column from its values
+fn centered(mut values: Vec<Vec<f64>>) -> Vec<Vec<f64>> {
+    for column in &mut values {
+        let mean = column.iter().sum::<f64>() / column.len() as f64;
+        column.iter_mut().for_each(|value| *value -= mean);
+    }
+    values
+}
+
+// Covariance of already centered columns
+fn covariance(centered: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let size = centered.len();
+    let rows = centered[0].len() as f64;
+    let mut matrix = vec![vec![0.0; size]; size];
+    for i in 0..size {
+        for j in i..size {
+            let sum: f64 = centered[i]
+                .iter()
+                .zip(&centered[j])
+                .map(|(x, y)| x * y)
+                .sum();
+            matrix[i][j] = sum / (rows - 1.0);
+            matrix[j][i] = matrix[i][j];
+        }
+    }
+    matrix
+}
+
+/// Eigenvalues and eigenvectors of a symmetric matrix by cyclic Jacobi
+/// rotations, which is accurate and simple for the small matrices a set of
+/// columns produces. Eigenvector `i` belongs to eigenvalue `i`.
+fn symmetric_eigen(matrix: &[Vec<f64>]) -> (Vec<f64>, Vec<Vec<f64>>) {
+    let size = matrix.len();
+    let mut a = matrix.to_vec();
+    let mut v: Vec<Vec<f64>> = (0..size)
+        .map(|i| (0..size).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect();
+
+    let norm: f64 = a.iter().flatten().map(|x| x * x).sum::<f64>().sqrt();
+    for _ in 0..100 {
+        let off_diagonal: f64 = (0..size)
+            .flat_map(|i| (0..size).filter(move |j| *j != i).map(move |j| (i, j)))
+            .map(|(i, j)| a[i][j] * a[i][j])
+            .sum::<f64>()
+            .sqrt();
+        if off_diagonal <= 1e-14 * norm {
+            break;
+        }
+
+        for p in 0..size {
+            for q in p + 1..size {
+                if a[p][q] == 0.0 {
+                    continue;
+                }
+
+                // Rotation angle that zeroes a[p][q]
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+
+                for row in a.iter_mut() {
+                    let (x, y) = (row[p], row[q]);
+                    row[p] = c * x - s * y;
+                    row[q] = s * x + c * y;
+                }
+                let (upper, lower) = a.split_at_mut(q);
+                for (x, y) in upper[p].iter_mut().zip(lower[0].iter_mut()) {
+                    (*x, *y) = (c * *x - s * *y, s * *x + c * *y);
+                }
+                for row in v.iter_mut() {
+                    let (x, y) = (row[p], row[q]);
+                    row[p] = c * x - s * y;
+                    row[q] = s * x + c * y;
+                }
+            }
+        }
+    }
+
+    let eigenvalues = (0..size).map(|i| a[i][i]).collect();
+    let eigenvectors = (0..size)
+        .map(|j| (0..size).map(|i| v[i][j]).collect())
+        .collect();
+    (eigenvalues, eigenvectors)
+}
+
+/// Linearly interpolated quantile of already sorted values.
+pub(crate) fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let position = q * (sorted.len() - 1) as f64;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+    let weight = position - lower as f64;
+
+    sorted[lower] + (sorted[upper] - sorted[lower]) * weight
+}
+
+/// Running central moments, updated one value at a time (Welford/Terriberry)
+/// so that no second pass over the data is needed.
+#[derive(Default)]
+pub(crate) struct Moments {
+    n: f64,
+    mean: f64,
+    m2: f64,
+    m3: f64,
+    m4: f64,
+}
+
+impl Moments {
+    pub(crate) fn push(&mut self, value: f64) {
+        let n1 = self.n;
+        self.n += 1.0;
+        let n = self.n;
+
+        let delta = value - self.mean;
+        let delta_n = delta / n;
+        let delta_n2 = delta_n * delta_n;
+        let term1 = delta * delta_n * n1;
+
+        self.mean += delta_n;
+        self.m4 += term1 * delta_n2 * (n * n - 3.0 * n + 3.0) + 6.0 * delta_n2 * self.m2
+            - 4.0 * delta_n * self.m3;
+        self.m3 += term1 * delta_n * (n - 2.0) - 3.0 * delta_n * self.m2;
+        self.m2 += term1;
+    }
 
-    // Compute variance (ddof=1 for sample variance)
-    let variance = series.var(1).expect("Failed to compute variance");
+    pub(crate) fn mean(&self) -> f64 {
+        self.mean
+    }
 
-    println!("Mean of '{}': {}", column_name, mean);
-    println!("Variance of '{}': {}", column_name, variance);
+    // Sample variance (ddof=1)
+    pub(crate) fn variance(&self) -> f64 {
+        self.m2 / (self.n - 1.0)
+    }
 
-    DataSummary { mean, variance }
-}
\ No newline at end of file
+    // Population skewness (g1), zero for constant values
+    pub(crate) fn skewness(&self) -> f64 {
+        if self.m2 == 0.0 {
+            return 0.0;
+        }
+        self.n.sqrt() * self.m3 / self.m2.powf(1.5)
+    }
+
+    // Excess kurtosis (g2), zero for a normal distribution and for constant
+    // values
+    pub(crate) fn kurtosis(&self) -> f64 {
+        if self.m2 == 0.0 {
+            return 0.0;
+        }
+        self.n * self.m4 / (self.m2 * self.m2) - 3.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-10,
+            "{} is not close to {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn quantile_interpolates_between_neighbours() {
+        let sorted = [1.0, 2.0, 4.0, 8.0];
+        assert_close(quantile(&sorted, 0.0), 1.0);
+        assert_close(quantile(&sorted, 0.25), 1.75);
+        assert_close(quantile(&sorted, 0.5), 3.0);
+        assert_close(quantile(&sorted, 1.0), 8.0);
+        assert_close(quantile(&[5.0], 0.75), 5.0);
+    }
+
+    #[test]
+    fn moments_match_two_pass_formulas() {
+        let values = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+        let mut moments = Moments::default();
+        for value in values {
+            moments.push(value);
+        }
+
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let central = |power: i32| values.iter().map(|v| (v - mean).powi(power)).sum::<f64>();
+        assert_close(moments.mean(), 5.0);
+        assert_close(moments.variance(), central(2) / (n - 1.0));
+        assert_close(
+            moments.skewness(),
+            n.sqrt() * central(3) / central(2).powf(1.5),
+        );
+        assert_close(
+            moments.kurtosis(),
+            n * central(4) / (central(2) * central(2)) - 3.0,
+        );
+    }
+
+    #[test]
+    fn constant_values_have_no_skewness_or_kurtosis() {
+        let mut moments = Moments::default();
+        for _ in 0..4 {
+            moments.push(3.5);
+        }
+
+        assert_close(moments.variance(), 0.0);
+        assert_close(moments.skewness(), 0.0);
+        assert_close(moments.kurtosis(), 0.0);
+    }
+
+    #[test]
+    fn summarize_values_of_the_readme_sample() {
+        // The values of data/large_dataset.csv, 10 to 55 in steps of 5
+        let mut values = [10.0, 20.0, 15.0, 30.0, 25.0, 35.0, 45.0, 40.0, 50.0, 55.0];
+        let summary = summarize_values("value", &mut values, 2);
+
+        assert_eq!(summary.count, 10);
+        assert_eq!(summary.null_count, 2);
+        assert_close(summary.min, 10.0);
+        assert_close(summary.max, 55.0);
+        assert_close(summary.q1, 21.25);
+        assert_close(summary.median, 32.5);
+        assert_close(summary.q3, 43.75);
+        assert_close(summary.iqr, 22.5);
+        assert_close(summary.mean, 32.5);
+        assert_close(summary.variance, 229.0 + 1.0 / 6.0);
+        assert_close(summary.std_dev, summary.variance.sqrt());
+        assert_close(summary.skewness, 0.0);
+        assert!((summary.kurtosis - -1.2242).abs() < 1e-4);
+    }
+
+    #[test]
+    fn summarize_values_of_a_single_value() {
+        let summary = summarize_values("value", &mut [7.0], 0);
+
+        assert_eq!(summary.count, 1);
+        for statistic in [
+            summary.min,
+            summary.max,
+            summary.median,
+            summary.q1,
+            summary.q3,
+        ] {
+            assert_close(statistic, 7.0);
+        }
+        assert_close(summary.iqr, 0.0);
+        assert_close(summary.mean, 7.0);
+        // The sample variance is undefined for one value
+        assert!(summary.variance.is_nan());
+        assert_close(summary.skewness, 0.0);
+        assert_close(summary.kurtosis, 0.0);
+    }
+
+    #[test]
+    fn all_null_columns_are_empty_data() {
+        let df = DataFrame::new(vec![Series::new("value", [None::<f64>, None])]).unwrap();
+
+        match summarize_column(&df, "value") {
+            Err(PipelineError::EmptyData(message)) => {
+                assert_eq!(message, "column 'value' has no non-null values")
+            }
+            other => panic!("expected an empty data error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn symmetric_eigen_of_a_tridiagonal_matrix() {
+        // Eigenvalues 2 - sqrt(2), 2 and 2 + sqrt(2)
+        let matrix = vec![
+            vec![2.0, -1.0, 0.0],
+            vec![-1.0, 2.0, -1.0],
+            vec![0.0, -1.0, 2.0],
+        ];
+        let (eigenvalues, eigenvectors) = symmetric_eigen(&matrix);
+
+        let mut sorted = eigenvalues.clone();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let root = std::f64::consts::SQRT_2;
+        for (actual, expected) in sorted.iter().zip([2.0 - root, 2.0, 2.0 + root]) {
+            assert_close(*actual, expected);
+        }
+
+        // Every pair satisfies A v = λ v with v of unit length
+        for (eigenvalue, vector) in eigenvalues.iter().zip(&eigenvectors) {
+            assert_close(vector.iter().map(|x| x * x).sum::<f64>(), 1.0);
+            for (row, weight) in matrix.iter().zip(vector) {
+                let product: f64 = row.iter().zip(vector).map(|(a, v)| a * v).sum();
+                assert_close(product, eigenvalue * weight);
+            }
+        }
+    }
+
+    #[test]
+    fn symmetric_eigen_of_a_diagonal_matrix_is_the_diagonal() {
+        let (eigenvalues, _) = symmetric_eigen(&[vec![3.0, 0.0], vec![0.0, -1.0]]);
+        assert_eq!(eigenvalues, vec![3.0, -1.0]);
+    }
+
+    #[test]
+    fn principal_components_of_proportional_columns() {
+        // y = 2x, so all the variance lies along (1, 2) / sqrt(5)
+        let frame = df!(
+            "x" => [1.0, 2.0, 3.0, 4.0],
+            "y" => [2.0, 4.0, 6.0, 8.0]
+        )
+        .unwrap();
+        let columns = ["x".to_string(), "y".to_string()];
+        let pca = principal_components(&frame, &columns, Some(1)).unwrap();
+
+        assert_close(pca.eigenvalues[0], 25.0 / 3.0);
+        assert_close(pca.eigenvalues[1], 0.0);
+        assert_close(pca.explained_variance_ratio[0], 1.0);
+        assert_close(pca.loadings[0][0], 1.0 / 5f64.sqrt());
+        assert_close(pca.loadings[0][1], 2.0 / 5f64.sqrt());
+
+        let scores = pca.scores.unwrap();
+        let pc1: Vec<f64> = scores
+            .column("PC1")
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        for (score, x) in pc1.iter().zip([-1.5, -0.5, 0.5, 1.5]) {
+            assert_close(*score, x * 5f64.sqrt());
+        }
+    }
+
+    #[test]
+    fn covariance_skips_rows_with_nulls() {
+        let frame = df!(
+            "x" => [Some(1.0), Some(2.0), None, Some(3.0)],
+            "y" => [Some(1.0), Some(3.0), Some(100.0), Some(2.0)]
+        )
+        .unwrap();
+        let columns = ["x".to_string(), "y".to_string()];
+        let matrix = covariance_matrix(&frame, &columns).unwrap();
+        assert_close(matrix[0][0], 1.0);
+        assert_close(matrix[0][1], 0.5);
+        assert_close(matrix[1][1], 1.0);
+    }
+}
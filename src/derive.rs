@@ -0,0 +1,213 @@
+// src/derive.rs
+
+use crate::error::PipelineError;
+use crate::expression::{is_identifier, parse_definition, Expression};
+use polars::prelude::*;
+use std::fmt;
+use std::str::FromStr;
+
+/// A column computed from the others before any statistics, written as
+/// `name = expression`, e.g. `log_value = log(value)` or
+/// `price = revenue / units`.
+#[derive(Clone, Debug)]
+pub struct DerivedColumn {
+    pub name: String,
+    pub expression: Expression,
+}
+
+impl DerivedColumn {
+    pub fn new(name: impl Into<String>, expression: Expression) -> Self {
+        DerivedColumn {
+            name: name.into(),
+            expression,
+        }
+    }
+
+    /// Parses a `name = expression` definition. Names that are not plain
+    /// identifiers go between backticks.
+    pub fn parse(definition: &str) -> Result<DerivedColumn, PipelineError> {
+        let (name, expression) = parse_definition(definition)?;
+        Ok(DerivedColumn::new(name, expression))
+    }
+}
+
+impl fmt::Display for DerivedColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if is_identifier(&self.name) {
+            write!(f, "{} = {}", self.name, self.expression)
+        } else {
+            write!(f, "`{}` = {}", self.name, self.expression)
+        }
+    }
+}
+
+impl FromStr for DerivedColumn {
+    type Err = PipelineError;
+
+    fn from_str(definition: &str) -> Result<Self, Self::Err> {
+        DerivedColumn::parse(definition)
+    }
+}
+
+/// Adds the derived columns to `df` in order, so that each one can use those
+/// defined before it. A column with the same name is replaced.
+pub fn derive_columns(
+    mut df: DataFrame,
+    derived: &[DerivedColumn],
+) -> Result<DataFrame, PipelineError> {
+    for column in derived {
+        let (expr, _) = column.expression.compile(&df.schema())?;
+        df = df.lazy().with_column(expr.alias(&column.name)).collect()?;
+    }
+    Ok(df)
+}
+
+/// The schema of a frame with `schema` once the derived columns are added.
+pub fn derived_schema(schema: &Schema, derived: &[DerivedColumn]) -> Result<Schema, PipelineError> {
+    Ok(derive_columns(DataFrame::from(schema), derived)?.schema())
+}
+
+/// The columns of the input needed to analyze `columns` after deriving, i.e.
+/// every column read that no earlier definition provides.
+pub fn input_columns(columns: &[String], derived: &[DerivedColumn]) -> Vec<String> {
+    let mut defined: Vec<&str> = Vec::new();
+    let mut needed: Vec<String> = Vec::new();
+    let mut need = |column: String, defined: &[&str]| {
+        if !defined.contains(&column.as_str()) && !needed.contains(&column) {
+            needed.push(column);
+        }
+    };
+
+    for definition in derived {
+        for column in definition.expression.columns() {
+            need(column, &defined);
+        }
+        defined.push(&definition.name);
+    }
+    for column in columns {
+        need(column.clone(), &defined);
+    }
+
+    needed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(definition: &str) -> String {
+        DerivedColumn::parse(definition).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn parse_reads_the_name_and_expression() {
+        let column = DerivedColumn::parse("ratio = a / (b + 1)").unwrap();
+        assert_eq!(column.name, "ratio");
+        assert_eq!(column.expression.to_string(), "a / (b + 1)");
+
+        let column = DerivedColumn::parse("  `unit price`=revenue / units ").unwrap();
+        assert_eq!(column.name, "unit price");
+        assert_eq!(column.expression.columns(), ["revenue", "units"]);
+
+        // Only the first `=` assigns, comparisons stay in the expression
+        let column = DerivedColumn::parse("big = if(a >= 10, 1, 0) == 1").unwrap();
+        assert_eq!(column.name, "big");
+        assert_eq!(column.expression.to_string(), "if(a >= 10, 1, 0) == 1");
+    }
+
+    #[test]
+    fn parse_rejects_malformed_definitions() {
+        assert!(error("= a").contains("expected a column name at position 1"));
+        assert!(error("2x = a").contains("'2x' is not a number"));
+        assert!(error("true = a").contains("expected a column name at position 1"));
+        assert!(error("`` = a").contains("expected a column name"));
+        assert!(error("a == b").contains("expected '=' after 'a' at position 3"));
+        assert!(error("a b = c").contains("expected '=' after 'a' at position 3"));
+        assert!(error("a").contains("expected '=' after 'a' at position 2"));
+        assert!(error("a = ").contains("expected a value at the end of 'a = '"));
+        assert!(error("a = b = c").contains("unexpected '=' at position 7"));
+    }
+
+    #[test]
+    fn display_quotes_names_that_need_it() {
+        for definition in [
+            "ratio = a / b",
+            "`unit price` = revenue / units",
+            "`null` = a",
+        ] {
+            let column = DerivedColumn::parse(definition).unwrap();
+            assert_eq!(column.to_string(), definition);
+            let reparsed = DerivedColumn::parse(&column.to_string()).unwrap();
+            assert_eq!(reparsed.name, column.name);
+        }
+    }
+
+    #[test]
+    fn input_columns_skip_the_derived_ones() {
+        let derived = [
+            DerivedColumn::parse("price = revenue / units").unwrap(),
+            DerivedColumn::parse("log_price = log(price)").unwrap(),
+            // Replaces an input column, which is still read for `units`
+            DerivedColumn::parse("units = units * 2").unwrap(),
+        ];
+
+        assert_eq!(
+            input_columns(&["log_price".to_string(), "margin".to_string()], &derived),
+            ["revenue", "units", "margin"]
+        );
+        assert_eq!(
+            input_columns(&["price".to_string()], &derived[..1]),
+            ["revenue", "units"]
+        );
+        assert!(input_columns(&[], &[]).is_empty());
+    }
+
+    #[test]
+    fn derive_columns_adds_and_replaces_in_order() {
+        let df = df!(
+            "revenue" => [10.0, 20.0, 30.0],
+            "units" => [2.0, 0.0, 3.0]
+        )
+        .unwrap();
+        let derived = [
+            DerivedColumn::parse("price = revenue / units").unwrap(),
+            DerivedColumn::parse("units = units + price").unwrap(),
+        ];
+
+        let df = derive_columns(df, &derived).unwrap();
+
+        assert_eq!(df.get_column_names(), ["revenue", "units", "price"]);
+        let column = |name: &str| -> Vec<Option<f64>> {
+            df.column(name)
+                .unwrap()
+                .f64()
+                .unwrap()
+                .into_iter()
+                .collect()
+        };
+        assert_eq!(column("price"), [Some(5.0), None, Some(10.0)]);
+        assert_eq!(column("units"), [Some(7.0), None, Some(13.0)]);
+    }
+
+    #[test]
+    fn derived_schema_types_the_new_columns() {
+        let schema = Schema::from_iter([
+            Field::new("name", DataType::Utf8),
+            Field::new("value", DataType::Int64),
+        ]);
+        let derived = [
+            DerivedColumn::parse("length = len(name)").unwrap(),
+            DerivedColumn::parse("positive = value > 0").unwrap(),
+        ];
+
+        let schema = derived_schema(&schema, &derived).unwrap();
+
+        assert!(schema.get("length").unwrap().is_numeric());
+        assert_eq!(schema.get("positive"), Some(&DataType::Boolean));
+        let unknown = [DerivedColumn::parse("x = missing + 1").unwrap()];
+        assert!(matches!(
+            derived_schema(&schema, &unknown),
+            Err(PipelineError::MissingColumn(column)) if column == "missing"
+        ));
+    }
+}
@@ -0,0 +1,112 @@
+// src/error.rs
+
+use arrow::error::ArrowError;
+use plotters::drawing::DrawingAreaErrorKind;
+use polars::prelude::PolarsError;
+use std::fmt;
+use std::io;
+
+/// Everything that can go wrong while loading, summarizing or plotting data.
+#[derive(Debug)]
+pub enum PipelineError {
+    Io(io::Error),
+    Parse(String),
+    MissingColumn(String),
+    TypeMismatch { column: String, dtype: String },
+    EmptyData(String),
+    Render(String),
+    Config(String),
+    Expression(String),
+    Query(String),
+}
+
+impl PipelineError {
+    /// Wraps an I/O error so that its message names the file involved.
+    pub fn io_at(file_path: &str, err: io::Error) -> Self {
+        PipelineError::Io(io::Error::new(
+            err.kind(),
+            format!("{}: {}", file_path, err),
+        ))
+    }
+
+    /// Exit code reported by the CLI, distinct per variant so that calling
+    /// scripts can react to the kind of failure. Codes 1 and 2 are left to
+    /// panics and clap usage errors.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            PipelineError::Io(_) => 3,
+            PipelineError::Parse(_) => 4,
+            PipelineError::MissingColumn(_) => 5,
+            PipelineError::TypeMismatch { .. } => 6,
+            PipelineError::EmptyData(_) => 7,
+            PipelineError::Render(_) => 8,
+            PipelineError::Config(_) => 9,
+            PipelineError::Expression(_) => 10,
+            PipelineError::Query(_) => 11,
+        }
+    }
+}
+
+impl fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PipelineError::Io(err) => write!(f, "I/O error: {}", err),
+            PipelineError::Parse(msg) => write!(f, "Failed to parse input: {}", msg),
+            PipelineError::MissingColumn(column) => write!(f, "Column '{}' not found", column),
+            PipelineError::TypeMismatch { column, dtype } => write!(
+                f,
+                "Column '{}' has type {} and cannot be analyzed as numeric",
+                column, dtype
+            ),
+            PipelineError::EmptyData(msg) => write!(f, "No data to analyze: {}", msg),
+            PipelineError::Render(msg) => write!(f, "Failed to render chart: {}", msg),
+            PipelineError::Config(msg) => write!(f, "Invalid pipeline configuration: {}", msg),
+            PipelineError::Expression(msg) => write!(f, "Invalid expression: {}", msg),
+            PipelineError::Query(msg) => write!(f, "Invalid SQL query: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for PipelineError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PipelineError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PipelineError {
+    fn from(err: io::Error) -> Self {
+        PipelineError::Io(err)
+    }
+}
+
+impl From<PolarsError> for PipelineError {
+    fn from(err: PolarsError) -> Self {
+        match err {
+            PolarsError::Io(err) => PipelineError::Io(err),
+            // Lazy plans append the failing query plan after the column name
+            PolarsError::ColumnNotFound(column) => {
+                PipelineError::MissingColumn(column.lines().next().unwrap_or_default().to_string())
+            }
+            PolarsError::NoData(msg) => PipelineError::EmptyData(msg.to_string()),
+            err => PipelineError::Parse(err.to_string()),
+        }
+    }
+}
+
+impl From<ArrowError> for PipelineError {
+    fn from(err: ArrowError) -> Self {
+        match err {
+            ArrowError::IoError(msg) => PipelineError::Io(io::Error::other(msg)),
+            err => PipelineError::Parse(err.to_string()),
+        }
+    }
+}
+
+impl<E: std::error::Error + Send + Sync> From<DrawingAreaErrorKind<E>> for PipelineError {
+    fn from(err: DrawingAreaErrorKind<E>) -> Self {
+        PipelineError::Render(err.to_string())
+    }
+}
@@ -0,0 +1,1196 @@
+// src/expression.rs
+
+use crate::error::PipelineError;
+use chrono::{NaiveDate, NaiveDateTime};
+use polars::prelude::*;
+use std::fmt;
+use std::str::FromStr;
+
+/// An expression over the columns of a frame, such as `log(revenue / units)`
+/// or `country == "DE" && value > 0`, compiled to a polars expression against
+/// the schema of the data it is applied to.
+///
+/// Operands are column names, numbers, double-quoted strings, `true`, `false`
+/// and `null`; column names that are not plain identifiers go between
+/// backticks. Operators, loosest binding first: `||`, `&&`, `!`, comparisons
+/// (`==`, `!=`, `<`, `<=`, `>`, `>=`), `+` and `-`, `*` and `/`, unary `-`.
+/// See `Function` for the functions that can be called.
+#[derive(Clone, Debug)]
+pub struct Expression {
+    source: String,
+    node: Node,
+}
+
+/// What an expression evaluates to, as far as type checking goes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValueKind {
+    Number,
+    Text,
+    Boolean,
+    /// Dates and datetimes
+    Temporal,
+    /// The `null` literal, compatible with every other kind
+    Null,
+}
+
+impl ValueKind {
+    fn of(dtype: &DataType) -> Option<ValueKind> {
+        match dtype {
+            dtype if dtype.is_numeric() => Some(ValueKind::Number),
+            DataType::Utf8 => Some(ValueKind::Text),
+            DataType::Boolean => Some(ValueKind::Boolean),
+            DataType::Date | DataType::Datetime(_, _) => Some(ValueKind::Temporal),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ValueKind::Number => "a number",
+            ValueKind::Text => "text",
+            ValueKind::Boolean => "a boolean",
+            ValueKind::Temporal => "a date",
+            ValueKind::Null => "null",
+        }
+    }
+}
+
+/// Functions expressions can call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Function {
+    /// `log(x)`: natural logarithm, null for x <= 0
+    Log,
+    /// `log10(x)`: base 10 logarithm, null for x <= 0
+    Log10,
+    /// `exp(x)`
+    Exp,
+    /// `sqrt(x)`: null for x < 0
+    Sqrt,
+    /// `abs(x)`
+    Abs,
+    /// `year(d)`, also for text parsed as a date
+    Year,
+    /// `month(d)`: 1 to 12
+    Month,
+    /// `day(d)`: day of the month
+    Day,
+    /// `weekday(d)`: 1 for Monday to 7 for Sunday
+    Weekday,
+    /// `hour(d)`
+    Hour,
+    /// `len(s)`: number of characters
+    Length,
+    /// `if(condition, then, otherwise)`
+    If,
+}
+
+impl Function {
+    const ALL: [Function; 12] = [
+        Function::Log,
+        Function::Log10,
+        Function::Exp,
+        Function::Sqrt,
+        Function::Abs,
+        Function::Year,
+        Function::Month,
+        Function::Day,
+        Function::Weekday,
+        Function::Hour,
+        Function::Length,
+        Function::If,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Function::Log => "log",
+            Function::Log10 => "log10",
+            Function::Exp => "exp",
+            Function::Sqrt => "sqrt",
+            Function::Abs => "abs",
+            Function::Year => "year",
+            Function::Month => "month",
+            Function::Day => "day",
+            Function::Weekday => "weekday",
+            Function::Hour => "hour",
+            Function::Length => "len",
+            Function::If => "if",
+        }
+    }
+
+    fn arity(&self) -> usize {
+        match self {
+            Function::If => 3,
+            _ => 1,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Node {
+    Column(String),
+    Number(f64),
+    Text(String),
+    Bool(bool),
+    Null,
+    Negate(Box<Node>),
+    Not(Box<Node>),
+    Binary(Operator, Box<Node>, Box<Node>),
+    Call(Function, Vec<Node>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operator {
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+impl Operator {
+    fn symbol(&self) -> &'static str {
+        match self {
+            Operator::Or => "||",
+            Operator::And => "&&",
+            Operator::Equal => "==",
+            Operator::NotEqual => "!=",
+            Operator::Less => "<",
+            Operator::LessOrEqual => "<=",
+            Operator::Greater => ">",
+            Operator::GreaterOrEqual => ">=",
+            Operator::Add => "+",
+            Operator::Subtract => "-",
+            Operator::Multiply => "*",
+            Operator::Divide => "/",
+        }
+    }
+
+    // How tightly the operator binds, as in the parser
+    fn precedence(&self) -> u8 {
+        match self {
+            Operator::Or => 1,
+            Operator::And => 2,
+            Operator::Add | Operator::Subtract => 5,
+            Operator::Multiply | Operator::Divide => 6,
+            _ => 4,
+        }
+    }
+
+    fn is_comparison(&self) -> bool {
+        matches!(
+            self,
+            Operator::Equal
+                | Operator::NotEqual
+                | Operator::Less
+                | Operator::LessOrEqual
+                | Operator::Greater
+                | Operator::GreaterOrEqual
+        )
+    }
+}
+
+impl Node {
+    // Operators bind as in the parser, with `!` between `&&` and the
+    // comparisons and unary `-` above `*` and `/`
+    fn precedence(&self) -> u8 {
+        match self {
+            Node::Binary(op, _, _) => op.precedence(),
+            Node::Not(_) => 3,
+            Node::Negate(_) => 7,
+            _ => 8,
+        }
+    }
+}
+
+// Writes `node` between parentheses when it binds looser than its context
+struct Grouped<'a>(&'a Node, bool);
+
+impl fmt::Display for Grouped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Grouped(node, true) => write!(f, "({})", node),
+            Grouped(node, false) => write!(f, "{}", node),
+        }
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let precedence = self.precedence();
+        match self {
+            Node::Column(name) if is_identifier(name) => write!(f, "{}", name),
+            Node::Column(name) => write!(f, "`{}`", name),
+            Node::Number(value) => write!(f, "{}", value),
+            Node::Text(value) => write!(f, "\"{}\"", value),
+            Node::Bool(value) => write!(f, "{}", value),
+            Node::Null => write!(f, "null"),
+            Node::Negate(inner) => {
+                write!(f, "-{}", Grouped(inner, inner.precedence() < precedence))
+            }
+            Node::Not(inner) => write!(f, "!{}", Grouped(inner, inner.precedence() < precedence)),
+            // Operators group to the left and comparisons do not chain
+            Node::Binary(op, left, right) => {
+                let left_open = left.precedence() < precedence
+                    || (op.is_comparison() && left.precedence() == precedence);
+                write!(
+                    f,
+                    "{} {} {}",
+                    Grouped(left, left_open),
+                    op.symbol(),
+                    Grouped(right, right.precedence() <= precedence)
+                )
+            }
+            Node::Call(function, args) => {
+                let args: Vec<String> = args.iter().map(Node::to_string).collect();
+                write!(f, "{}({})", function.name(), args.join(", "))
+            }
+        }
+    }
+}
+
+impl Expression {
+    /// Parses an expression, reporting syntax errors with their position.
+    pub fn parse(source: &str) -> Result<Expression, PipelineError> {
+        let tokens = tokenize(source)?;
+        Ok(Expression {
+            source: source.to_string(),
+            node: parse_tokens(&tokens, source)?,
+        })
+    }
+
+    /// Columns the expression reads, in order of first use.
+    pub fn columns(&self) -> Vec<String> {
+        let mut columns = Vec::new();
+        collect_columns(&self.node, &mut columns);
+        columns
+    }
+
+    /// Compiles the expression to a polars expression, checking that every
+    /// column exists in `schema` and every operand has a fitting type.
+    pub fn compile(&self, schema: &Schema) -> Result<(Expr, ValueKind), PipelineError> {
+        compile(&self.node, schema)
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl FromStr for Expression {
+    type Err = PipelineError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        Expression::parse(source)
+    }
+}
+
+/// Parses a `name = expression` definition, where the name is an identifier
+/// or a column name between backticks, and returns both. Syntax errors give
+/// their position in `definition`.
+pub(crate) fn parse_definition(definition: &str) -> Result<(String, Expression), PipelineError> {
+    let tokens = tokenize(definition)?;
+    let at = |index: usize| {
+        tokens
+            .get(index)
+            .map_or(definition.chars().count(), |(_, position)| *position)
+    };
+
+    let name = match tokens.first() {
+        Some((Token::Identifier(name), _)) if is_identifier(name) => name.clone(),
+        Some((Token::Quoted(name), _)) if !name.is_empty() => name.clone(),
+        _ => return Err(syntax_error(definition, at(0), "expected a column name")),
+    };
+    if !matches!(tokens.get(1), Some((Token::Assign, _))) {
+        return Err(syntax_error(
+            definition,
+            at(1),
+            &format!("expected '=' after '{}'", name),
+        ));
+    }
+
+    let source: String = definition.chars().skip(at(2)).collect();
+    let expression = Expression {
+        source: source.trim_end().to_string(),
+        node: parse_tokens(&tokens[2..], definition)?,
+    };
+    Ok((name, expression))
+}
+
+// The expression of all of `tokens`, with errors pointing into `source`
+fn parse_tokens(tokens: &[(Token, usize)], source: &str) -> Result<Node, PipelineError> {
+    let mut parser = Parser {
+        tokens,
+        position: 0,
+        source,
+    };
+    let node = parser.or()?;
+    if let Some(token) = parser.peek() {
+        return Err(parser.unexpected(token));
+    }
+    Ok(node)
+}
+
+/// Whether `name` can be written without backticks.
+pub fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || matches!(c, '_' | '.'))
+        && !matches!(name, "true" | "false" | "null")
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Identifier(String),
+    /// A column name between backticks, never a keyword or function
+    Quoted(String),
+    Number(f64),
+    Text(String),
+    Operator(Operator),
+    /// A single `=`, only valid after the name of a derived column
+    Assign,
+    Minus,
+    Not,
+    Open,
+    Close,
+    Comma,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Identifier(name) => write!(f, "{}", name),
+            Token::Quoted(name) => write!(f, "`{}`", name),
+            Token::Number(value) => write!(f, "{}", value),
+            Token::Text(value) => write!(f, "\"{}\"", value),
+            Token::Operator(op) => write!(f, "{}", op.symbol()),
+            Token::Assign => write!(f, "="),
+            Token::Minus => write!(f, "-"),
+            Token::Not => write!(f, "!"),
+            Token::Open => write!(f, "("),
+            Token::Close => write!(f, ")"),
+            Token::Comma => write!(f, ","),
+        }
+    }
+}
+
+// Tokens paired with the character offset they start at
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, PipelineError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut index = 0;
+
+    while index < chars.len() {
+        let start = index;
+        let next = chars.get(index + 1).copied();
+        let token = match chars[index] {
+            c if c.is_whitespace() => {
+                index += 1;
+                continue;
+            }
+            '(' => Token::Open,
+            ')' => Token::Close,
+            ',' => Token::Comma,
+            '+' => Token::Operator(Operator::Add),
+            '-' => Token::Minus,
+            '*' => Token::Operator(Operator::Multiply),
+            '/' => Token::Operator(Operator::Divide),
+            '&' if next == Some('&') => Token::Operator(Operator::And),
+            '|' if next == Some('|') => Token::Operator(Operator::Or),
+            '=' if next == Some('=') => Token::Operator(Operator::Equal),
+            '!' if next == Some('=') => Token::Operator(Operator::NotEqual),
+            '<' if next == Some('=') => Token::Operator(Operator::LessOrEqual),
+            '>' if next == Some('=') => Token::Operator(Operator::GreaterOrEqual),
+            '!' => Token::Not,
+            '<' => Token::Operator(Operator::Less),
+            '>' => Token::Operator(Operator::Greater),
+            '=' => Token::Assign,
+            quote @ ('"' | '`') => {
+                let end = chars[start + 1..]
+                    .iter()
+                    .position(|c| *c == quote)
+                    .map(|offset| start + 1 + offset)
+                    .ok_or_else(|| {
+                        syntax_error(source, start, &format!("unterminated {}", quote))
+                    })?;
+                let text: String = chars[start + 1..end].iter().collect();
+                index = end + 1;
+                tokens.push((
+                    if quote == '"' {
+                        Token::Text(text)
+                    } else {
+                        Token::Quoted(text)
+                    },
+                    start,
+                ));
+                continue;
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                index += 1;
+                while index < chars.len() {
+                    let c = chars[index];
+                    let exponent_sign =
+                        (c == '-' || c == '+') && matches!(chars[index - 1], 'e' | 'E');
+                    if c.is_ascii_alphanumeric() || c == '.' || exponent_sign {
+                        index += 1;
+                    } else {
+                        break;
+                    }
+                }
+                let text: String = chars[start..index].iter().collect();
+                let value = text.parse::<f64>().map_err(|_| {
+                    syntax_error(source, start, &format!("'{}' is not a number", text))
+                })?;
+                tokens.push((Token::Number(value), start));
+                continue;
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                while index < chars.len()
+                    && (chars[index].is_alphanumeric() || matches!(chars[index], '_' | '.'))
+                {
+                    index += 1;
+                }
+                let name: String = chars[start..index].iter().collect();
+                tokens.push((Token::Identifier(name), start));
+                continue;
+            }
+            c => {
+                return Err(syntax_error(
+                    source,
+                    start,
+                    &format!("unexpected character '{}'", c),
+                ))
+            }
+        };
+
+        index += token.to_string().chars().count();
+        tokens.push((token, start));
+    }
+
+    Ok(tokens)
+}
+
+fn syntax_error(source: &str, position: usize, message: &str) -> PipelineError {
+    PipelineError::Expression(format!(
+        "{} at position {} of '{}'",
+        message,
+        position + 1,
+        source
+    ))
+}
+
+// Recursive descent with one method per precedence level
+struct Parser<'a> {
+    tokens: &'a [(Token, usize)],
+    position: usize,
+    source: &'a str,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&(Token, usize)> {
+        self.tokens.get(self.position)
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        match self.peek() {
+            Some((next, _)) if next == token => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // The next token if it is one of `operators`
+    fn eat_operator(&mut self, operators: &[Operator]) -> Option<Operator> {
+        let op = match self.peek() {
+            Some((Token::Operator(op), _)) if operators.contains(op) => *op,
+            Some((Token::Minus, _)) if operators.contains(&Operator::Subtract) => {
+                Operator::Subtract
+            }
+            _ => return None,
+        };
+        self.position += 1;
+        Some(op)
+    }
+
+    fn unexpected(&self, (token, position): &(Token, usize)) -> PipelineError {
+        syntax_error(self.source, *position, &format!("unexpected '{}'", token))
+    }
+
+    fn end_error(&self, expected: &str) -> PipelineError {
+        PipelineError::Expression(format!(
+            "expected {} at the end of '{}'",
+            expected, self.source
+        ))
+    }
+
+    fn expect(&mut self, token: Token) -> Result<(), PipelineError> {
+        match self.peek() {
+            Some((next, _)) if *next == token => {
+                self.position += 1;
+                Ok(())
+            }
+            Some(next) => Err(self.unexpected(next)),
+            None => Err(self.end_error(&format!("'{}'", token))),
+        }
+    }
+
+    fn binary(
+        &mut self,
+        operators: &[Operator],
+        operand: fn(&mut Self) -> Result<Node, PipelineError>,
+    ) -> Result<Node, PipelineError> {
+        let mut node = operand(self)?;
+        while let Some(op) = self.eat_operator(operators) {
+            node = Node::Binary(op, Box::new(node), Box::new(operand(self)?));
+        }
+        Ok(node)
+    }
+
+    fn or(&mut self) -> Result<Node, PipelineError> {
+        self.binary(&[Operator::Or], Self::and)
+    }
+
+    fn and(&mut self) -> Result<Node, PipelineError> {
+        self.binary(&[Operator::And], Self::not)
+    }
+
+    fn not(&mut self) -> Result<Node, PipelineError> {
+        if self.eat(&Token::Not) {
+            return Ok(Node::Not(Box::new(self.not()?)));
+        }
+        self.comparison()
+    }
+
+    // Comparisons do not chain: `a < b < c` is an error
+    fn comparison(&mut self) -> Result<Node, PipelineError> {
+        let left = self.additive()?;
+        match self.peek() {
+            Some((Token::Operator(op), _)) if op.is_comparison() => {
+                let op = *op;
+                self.position += 1;
+                Ok(Node::Binary(op, Box::new(left), Box::new(self.additive()?)))
+            }
+            _ => Ok(left),
+        }
+    }
+
+    fn additive(&mut self) -> Result<Node, PipelineError> {
+        self.binary(&[Operator::Add, Operator::Subtract], Self::multiplicative)
+    }
+
+    fn multiplicative(&mut self) -> Result<Node, PipelineError> {
+        self.binary(&[Operator::Multiply, Operator::Divide], Self::unary)
+    }
+
+    fn unary(&mut self) -> Result<Node, PipelineError> {
+        if self.eat(&Token::Minus) {
+            return Ok(match self.unary()? {
+                Node::Number(value) => Node::Number(-value),
+                node => Node::Negate(Box::new(node)),
+            });
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Node, PipelineError> {
+        let (token, position) = self
+            .peek()
+            .cloned()
+            .ok_or_else(|| self.end_error("a value"))?;
+        self.position += 1;
+
+        Ok(match token {
+            Token::Number(value) => Node::Number(value),
+            Token::Text(value) => Node::Text(value),
+            Token::Quoted(name) => Node::Column(name),
+            Token::Open => {
+                let node = self.or()?;
+                self.expect(Token::Close)?;
+                node
+            }
+            Token::Identifier(name) if self.eat(&Token::Open) => {
+                let function = Function::ALL
+                    .into_iter()
+                    .find(|function| function.name() == name)
+                    .ok_or_else(|| {
+                        syntax_error(
+                            self.source,
+                            position,
+                            &format!("unknown function '{}'", name),
+                        )
+                    })?;
+
+                let mut args = Vec::new();
+                if !self.eat(&Token::Close) {
+                    args.push(self.or()?);
+                    while self.eat(&Token::Comma) {
+                        args.push(self.or()?);
+                    }
+                    self.expect(Token::Close)?;
+                }
+                if args.len() != function.arity() {
+                    return Err(syntax_error(
+                        self.source,
+                        position,
+                        &format!(
+                            "{} takes {} argument(s), got {}",
+                            name,
+                            function.arity(),
+                            args.len()
+                        ),
+                    ));
+                }
+                Node::Call(function, args)
+            }
+            Token::Identifier(name) => match name.as_str() {
+                "true" => Node::Bool(true),
+                "false" => Node::Bool(false),
+                "null" => Node::Null,
+                _ => Node::Column(name),
+            },
+            token => return Err(self.unexpected(&(token, position))),
+        })
+    }
+}
+
+fn collect_columns(node: &Node, columns: &mut Vec<String>) {
+    match node {
+        Node::Column(name) => {
+            if !columns.contains(name) {
+                columns.push(name.clone());
+            }
+        }
+        Node::Negate(inner) | Node::Not(inner) => collect_columns(inner, columns),
+        Node::Binary(_, left, right) => {
+            collect_columns(left, columns);
+            collect_columns(right, columns);
+        }
+        Node::Call(_, args) => args.iter().for_each(|arg| collect_columns(arg, columns)),
+        Node::Number(_) | Node::Text(_) | Node::Bool(_) | Node::Null => {}
+    }
+}
+
+fn type_error(message: String) -> PipelineError {
+    PipelineError::Expression(message)
+}
+
+// Compiles `node` and checks that it evaluates to `expected`
+fn compile_as(
+    node: &Node,
+    expected: ValueKind,
+    context: &str,
+    schema: &Schema,
+) -> Result<Expr, PipelineError> {
+    let (expr, kind) = compile(node, schema)?;
+    if kind != expected && kind != ValueKind::Null {
+        return Err(type_error(format!(
+            "{} needs {}, but {} is {}",
+            context,
+            expected.name(),
+            node,
+            kind.name()
+        )));
+    }
+    Ok(expr)
+}
+
+fn compile(node: &Node, schema: &Schema) -> Result<(Expr, ValueKind), PipelineError> {
+    let number = |node: &Node, context: &str| -> Result<Expr, PipelineError> {
+        Ok(compile_as(node, ValueKind::Number, context, schema)?.cast(DataType::Float64))
+    };
+    let boolean =
+        |node: &Node, context: &str| compile_as(node, ValueKind::Boolean, context, schema);
+
+    Ok(match node {
+        Node::Column(name) => {
+            let dtype = schema
+                .get(name)
+                .ok_or_else(|| PipelineError::MissingColumn(name.clone()))?;
+            let kind = ValueKind::of(dtype).ok_or_else(|| {
+                type_error(format!(
+                    "column '{}' has type {}, which expressions do not support",
+                    name, dtype
+                ))
+            })?;
+            (col(name), kind)
+        }
+        Node::Number(value) => (lit(*value), ValueKind::Number),
+        Node::Text(value) => (lit(value.as_str()), ValueKind::Text),
+        Node::Bool(value) => (lit(*value), ValueKind::Boolean),
+        Node::Null => (lit(NULL), ValueKind::Null),
+        Node::Negate(inner) => (lit(0.0) - number(inner, "-")?, ValueKind::Number),
+        Node::Not(inner) => (boolean(inner, "!")?.not(), ValueKind::Boolean),
+        Node::Binary(op, left, right) => match op {
+            Operator::Or => (
+                boolean(left, "||")?.or(boolean(right, "||")?),
+                ValueKind::Boolean,
+            ),
+            Operator::And => (
+                boolean(left, "&&")?.and(boolean(right, "&&")?),
+                ValueKind::Boolean,
+            ),
+            Operator::Add => (number(left, "+")? + number(right, "+")?, ValueKind::Number),
+            Operator::Subtract => (number(left, "-")? - number(right, "-")?, ValueKind::Number),
+            Operator::Multiply => (number(left, "*")? * number(right, "*")?, ValueKind::Number),
+            // Division by zero gives null rather than an infinity the
+            // statistics and charts cannot use
+            Operator::Divide => {
+                let divisor = number(right, "/")?;
+                (
+                    when(divisor.clone().eq(lit(0.0)))
+                        .then(lit(NULL))
+                        .otherwise(number(left, "/")? / divisor),
+                    ValueKind::Number,
+                )
+            }
+            op => (compare(*op, left, right, schema)?, ValueKind::Boolean),
+        },
+        Node::Call(function, args) => call(*function, args, schema)?,
+    })
+}
+
+fn compare(
+    op: Operator,
+    left: &Node,
+    right: &Node,
+    schema: &Schema,
+) -> Result<Expr, PipelineError> {
+    let (mut left_expr, mut left_kind) = compile(left, schema)?;
+    let (mut right_expr, mut right_kind) = compile(right, schema)?;
+
+    // Text literals compared with dates are read as dates, as in
+    // `day >= "2024-01-31"`
+    if let (Node::Text(text), ValueKind::Temporal) = (left, right_kind) {
+        (left_expr, left_kind) = (temporal_literal(text)?, ValueKind::Temporal);
+    }
+    if let (ValueKind::Temporal, Node::Text(text)) = (left_kind, right) {
+        (right_expr, right_kind) = (temporal_literal(text)?, ValueKind::Temporal);
+    }
+
+    // `== null` and `!= null` test for missing values
+    let equality = matches!(op, Operator::Equal | Operator::NotEqual);
+    match (left_kind, right_kind) {
+        (ValueKind::Null, ValueKind::Null) => {
+            return Err(type_error(format!(
+                "{} {} {} compares null with null",
+                left,
+                op.symbol(),
+                right
+            )))
+        }
+        (ValueKind::Null, _) | (_, ValueKind::Null) if equality => {
+            let tested = if left_kind == ValueKind::Null {
+                right_expr
+            } else {
+                left_expr
+            };
+            return Ok(match op {
+                Operator::Equal => tested.is_null(),
+                _ => tested.is_not_null(),
+            });
+        }
+        (left_kind, right_kind)
+            if left_kind == right_kind && (left_kind != ValueKind::Boolean || equality) => {}
+        _ => {
+            return Err(type_error(format!(
+                "cannot compare {} ({}) with {} ({}) using {}",
+                left,
+                left_kind.name(),
+                right,
+                right_kind.name(),
+                op.symbol()
+            )))
+        }
+    }
+
+    Ok(match op {
+        Operator::Equal => left_expr.eq(right_expr),
+        Operator::NotEqual => left_expr.neq(right_expr),
+        Operator::Less => left_expr.lt(right_expr),
+        Operator::LessOrEqual => left_expr.lt_eq(right_expr),
+        Operator::Greater => left_expr.gt(right_expr),
+        _ => left_expr.gt_eq(right_expr),
+    })
+}
+
+// A date such as "2024-01-31" or a datetime such as "2024-01-31 08:30:00"
+fn temporal_literal(text: &str) -> Result<Expr, PipelineError> {
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        return Ok(lit(date).cast(DataType::Date));
+    }
+    [
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+    .map(lit)
+    .ok_or_else(|| {
+        type_error(format!(
+            "cannot compare a date with \"{}\", expected a date such as \"2024-01-31\" or a \
+             datetime such as \"2024-01-31 08:30:00\"",
+            text
+        ))
+    })
+}
+
+fn call(
+    function: Function,
+    args: &[Node],
+    schema: &Schema,
+) -> Result<(Expr, ValueKind), PipelineError> {
+    let context = format!("{}()", function.name());
+    let number = |node: &Node| -> Result<Expr, PipelineError> {
+        Ok(compile_as(node, ValueKind::Number, &context, schema)?.cast(DataType::Float64))
+    };
+    // Null outside the domain of the logarithms and the square root
+    let guarded = |node: &Node, domain: fn(Expr) -> Expr, f: fn(Expr) -> Expr| {
+        let x = number(node)?;
+        Ok::<_, PipelineError>(when(domain(x.clone())).then(f(x)).otherwise(lit(NULL)))
+    };
+
+    Ok(match function {
+        Function::Log => (
+            guarded(&args[0], |x| x.gt(lit(0.0)), |x| x.log(std::f64::consts::E))?,
+            ValueKind::Number,
+        ),
+        Function::Log10 => (
+            guarded(&args[0], |x| x.gt(lit(0.0)), |x| x.log(10.0))?,
+            ValueKind::Number,
+        ),
+        Function::Sqrt => (
+            guarded(&args[0], |x| x.gt_eq(lit(0.0)), |x| x.pow(0.5))?,
+            ValueKind::Number,
+        ),
+        Function::Exp => (number(&args[0])?.exp(), ValueKind::Number),
+        Function::Abs => (number(&args[0])?.abs(), ValueKind::Number),
+        Function::Year | Function::Month | Function::Day | Function::Weekday | Function::Hour => {
+            let date = temporal(&args[0], &context, schema)?.dt();
+            let part = match function {
+                Function::Year => date.year(),
+                Function::Month => date.month(),
+                Function::Day => date.day(),
+                Function::Weekday => date.weekday(),
+                _ => date.hour(),
+            };
+            (part.cast(DataType::Float64), ValueKind::Number)
+        }
+        Function::Length => {
+            let text = compile_as(&args[0], ValueKind::Text, &context, schema)?;
+            let length = text.map(
+                |series| Ok(Some(series.utf8()?.str_n_chars().into_series())),
+                GetOutput::from_type(DataType::UInt32),
+            );
+            (length, ValueKind::Number)
+        }
+        Function::If => {
+            let condition = compile_as(&args[0], ValueKind::Boolean, &context, schema)?;
+            let (then, then_kind) = compile(&args[1], schema)?;
+            let (otherwise, otherwise_kind) = compile(&args[2], schema)?;
+            let kind = match (then_kind, otherwise_kind) {
+                (ValueKind::Null, kind) | (kind, ValueKind::Null) => kind,
+                (then_kind, otherwise_kind) if then_kind == otherwise_kind => then_kind,
+                _ => {
+                    return Err(type_error(format!(
+                        "the branches of {} are {} and {}",
+                        Node::Call(function, args.to_vec()),
+                        then_kind.name(),
+                        otherwise_kind.name()
+                    )))
+                }
+            };
+            (when(condition).then(then).otherwise(otherwise), kind)
+        }
+    })
+}
+
+// Dates stored as text are parsed, unparsable ones become null
+fn temporal(node: &Node, context: &str, schema: &Schema) -> Result<Expr, PipelineError> {
+    let (expr, kind) = compile(node, schema)?;
+    match kind {
+        ValueKind::Temporal | ValueKind::Null => Ok(expr),
+        ValueKind::Text => Ok(expr.str().strptime(
+            DataType::Datetime(TimeUnit::Microseconds, None),
+            StrptimeOptions {
+                strict: false,
+                ..Default::default()
+            },
+        )),
+        kind => Err(type_error(format!(
+            "{} needs a date, but {} is {}",
+            context,
+            node,
+            kind.name()
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn printed(source: &str) -> String {
+        Expression::parse(source).unwrap().node.to_string()
+    }
+
+    fn evaluate(source: &str) -> Vec<Option<f64>> {
+        let frame = df!(
+            "a" => [4.0, -1.0, 0.0],
+            "b" => [Some(2.0), Some(0.0), None],
+            "name" => ["DE", "Zürich", ""],
+            "day" => ["2024-03-05", "2023-12-31", "not a date"]
+        )
+        .unwrap();
+        let (expr, kind) = Expression::parse(source)
+            .unwrap()
+            .compile(&frame.schema())
+            .unwrap();
+        assert_eq!(kind, ValueKind::Number, "{}", source);
+        let result = frame
+            .lazy()
+            .select([expr.cast(DataType::Float64).alias("result")])
+            .collect()
+            .unwrap();
+        result
+            .column("result")
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_iter()
+            .collect()
+    }
+
+    fn error(source: &str) -> String {
+        Expression::parse(source).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn display_keeps_the_parentheses_precedence_needs() {
+        assert_eq!(printed("(a + b) * c"), "(a + b) * c");
+        assert_eq!(printed("a + b * c"), "a + b * c");
+        assert_eq!(printed("a - (b - c)"), "a - (b - c)");
+        assert_eq!(printed("(a - b) - c"), "a - b - c");
+        assert_eq!(printed("a / (b * c)"), "a / (b * c)");
+        assert_eq!(printed("-(a + b)"), "-(a + b)");
+        assert_eq!(printed("!(a || b) && c"), "!(a || b) && c");
+        assert_eq!(printed("(x > 1) == flag"), "(x > 1) == flag");
+        assert_eq!(printed("(a || b) && (c || d)"), "(a || b) && (c || d)");
+        assert_eq!(printed("log((a + 1) * 2)"), "log((a + 1) * 2)");
+    }
+
+    #[test]
+    fn type_errors_show_the_grouping() {
+        let schema = Schema::from_iter([
+            Field::new("a", DataType::Float64),
+            Field::new("b", DataType::Float64),
+            Field::new("c", DataType::Float64),
+        ]);
+        let err = Expression::parse("(a + b) * c == \"x\"")
+            .unwrap()
+            .compile(&schema)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid expression: cannot compare (a + b) * c (a number) with \"x\" (text) using =="
+        );
+    }
+    #[test]
+    fn tokenizer_splits_operators_numbers_and_quoted_names() {
+        let tokens: Vec<Token> = tokenize("a>=1.5e-3&&!`x y`!=\"s\"")
+            .unwrap()
+            .into_iter()
+            .map(|(token, _)| token)
+            .collect();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Identifier("a".to_string()),
+                Token::Operator(Operator::GreaterOrEqual),
+                Token::Number(1.5e-3),
+                Token::Operator(Operator::And),
+                Token::Not,
+                Token::Quoted("x y".to_string()),
+                Token::Operator(Operator::NotEqual),
+                Token::Text("s".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn tokens_record_character_positions() {
+        let positions: Vec<usize> = tokenize("é + `ü` * 2")
+            .unwrap()
+            .into_iter()
+            .map(|(_, position)| position)
+            .collect();
+        assert_eq!(positions, vec![0, 2, 4, 8, 10]);
+    }
+
+    #[test]
+    fn operators_bind_by_precedence() {
+        assert_eq!(evaluate("a + b * 2"), vec![Some(8.0), Some(-1.0), None]);
+        assert_eq!(evaluate("(a + b) * 2"), vec![Some(12.0), Some(-2.0), None]);
+        assert_eq!(
+            evaluate("a - 1 - 1"),
+            vec![Some(2.0), Some(-3.0), Some(-2.0)]
+        );
+        assert_eq!(evaluate("-a * 2"), vec![Some(-8.0), Some(2.0), Some(0.0)]);
+        assert_eq!(
+            evaluate("if(a > 0 || a < 0 && b == 0, 1, 0)"),
+            vec![Some(1.0), Some(1.0), Some(0.0)]
+        );
+        assert_eq!(
+            evaluate("if(!a > 0 && b != null, 1, 0)"),
+            vec![Some(0.0), Some(1.0), Some(0.0)]
+        );
+    }
+
+    #[test]
+    fn functions_give_null_outside_their_domain() {
+        assert_eq!(evaluate("a / b"), vec![Some(2.0), None, None]);
+        assert_eq!(evaluate("log(a)"), vec![Some(4f64.ln()), None, None]);
+        assert_eq!(evaluate("sqrt(a)"), vec![Some(2.0), None, Some(0.0)]);
+        assert_eq!(evaluate("abs(a)"), vec![Some(4.0), Some(1.0), Some(0.0)]);
+        assert_eq!(evaluate("len(name)"), vec![Some(2.0), Some(6.0), Some(0.0)]);
+        assert_eq!(
+            evaluate("year(day)"),
+            vec![Some(2024.0), Some(2023.0), None]
+        );
+        assert_eq!(evaluate("month(day)"), vec![Some(3.0), Some(12.0), None]);
+        assert_eq!(evaluate("weekday(day)"), vec![Some(2.0), Some(7.0), None]);
+    }
+
+    #[test]
+    fn syntax_errors_point_at_the_offending_token() {
+        assert_eq!(
+            error("a + * b"),
+            "Invalid expression: unexpected '*' at position 5 of 'a + * b'"
+        );
+        assert_eq!(
+            error("é $ b"),
+            "Invalid expression: unexpected character '$' at position 3 of 'é $ b'"
+        );
+        assert_eq!(
+            error("1 < 2 < 3"),
+            "Invalid expression: unexpected '<' at position 7 of '1 < 2 < 3'"
+        );
+        assert_eq!(
+            error("name == \"DE"),
+            "Invalid expression: unterminated \" at position 9 of 'name == \"DE'"
+        );
+        assert_eq!(
+            error("1.2.3 + a"),
+            "Invalid expression: '1.2.3' is not a number at position 1 of '1.2.3 + a'"
+        );
+        assert_eq!(
+            error("a + foo(b)"),
+            "Invalid expression: unknown function 'foo' at position 5 of 'a + foo(b)'"
+        );
+        assert_eq!(
+            error("log(a, b)"),
+            "Invalid expression: log takes 1 argument(s), got 2 at position 1 of 'log(a, b)'"
+        );
+        assert_eq!(
+            error("(a + b"),
+            "Invalid expression: expected ')' at the end of '(a + b'"
+        );
+        assert_eq!(
+            error("a *"),
+            "Invalid expression: expected a value at the end of 'a *'"
+        );
+    }
+
+    #[test]
+    fn compile_checks_columns_and_types() {
+        let schema = Schema::from_iter([
+            Field::new("value", DataType::Int64),
+            Field::new("country", DataType::Utf8),
+        ]);
+        let compile = |source: &str| Expression::parse(source).unwrap().compile(&schema);
+
+        assert!(matches!(
+            compile("missing + 1"),
+            Err(PipelineError::MissingColumn(column)) if column == "missing"
+        ));
+        assert_eq!(
+            compile("country * 2").unwrap_err().to_string(),
+            "Invalid expression: * needs a number, but country is text"
+        );
+        assert_eq!(
+            compile("value && true").unwrap_err().to_string(),
+            "Invalid expression: && needs a boolean, but value is a number"
+        );
+        assert_eq!(
+            compile("if(value > 0, value, country)").unwrap_err().to_string(),
+            "Invalid expression: the branches of if(value > 0, value, country) are a number and text"
+        );
+        assert_eq!(
+            compile("country == \"DE\" && value > 0").unwrap().1,
+            ValueKind::Boolean
+        );
+        assert_eq!(
+            Expression::parse("`unit price` / value").unwrap().columns(),
+            vec!["unit price".to_string(), "value".to_string()]
+        );
+    }
+
+    #[test]
+    fn text_literals_compare_with_dates() {
+        let day = Series::new("day", [Some(19_723), Some(19_724), None])
+            .cast(&DataType::Date)
+            .unwrap();
+        let at = Series::new(
+            "at",
+            [Some(1_704_067_200_000_i64), Some(1_704_110_400_000), None],
+        )
+        .cast(&DataType::Datetime(TimeUnit::Milliseconds, None))
+        .unwrap();
+        let frame = DataFrame::new(vec![day, at, Series::new("name", ["a", "b", "c"])]).unwrap();
+        let matching = |source: &str| {
+            let (expr, kind) = Expression::parse(source)
+                .unwrap()
+                .compile(&frame.schema())
+                .unwrap();
+            assert_eq!(kind, ValueKind::Boolean, "{}", source);
+            frame
+                .clone()
+                .lazy()
+                .filter(expr)
+                .collect()
+                .unwrap()
+                .height()
+        };
+
+        // 2024-01-01 and 2024-01-02, then 2024-01-01 00:00 and 12:00
+        assert_eq!(matching(r#"day == "2024-01-01""#), 1);
+        assert_eq!(matching(r#"day > "2024-01-01""#), 1);
+        assert_eq!(matching(r#""2024-01-02" >= day"#), 2);
+        assert_eq!(matching(r#"at < "2024-01-01 12:00:00""#), 1);
+        assert_eq!(matching(r#"at >= "2024-01-01T06:30""#), 1);
+        assert_eq!(matching(r#"at <= "2024-01-01 12:00:00.5""#), 2);
+        assert_eq!(matching(r#"day != null && day < "2024-01-02""#), 1);
+
+        let error = |source: &str| {
+            Expression::parse(source)
+                .unwrap()
+                .compile(&frame.schema())
+                .unwrap_err()
+                .to_string()
+        };
+        assert!(error(r#"day > "soon""#).contains(r#"cannot compare a date with "soon""#));
+        assert!(error(r#"day > "2024-13-01""#).contains("expected a date such as"));
+        assert!(error("day == name").contains("cannot compare day (a date) with name (text)"));
+    }
+}
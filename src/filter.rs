@@ -0,0 +1,114 @@
+// src/filter.rs
+
+use crate::error::PipelineError;
+use crate::expression::{Expression, ValueKind};
+use polars::prelude::*;
+use std::fmt;
+use std::str::FromStr;
+
+/// A row filter such as `country == "DE" && value > 0`: an `Expression` that
+/// evaluates to a boolean.
+#[derive(Clone, Debug)]
+pub struct Filter {
+    expression: Expression,
+}
+
+impl Filter {
+    /// Parses a filter expression, reporting syntax errors with their position.
+    pub fn parse(source: &str) -> Result<Filter, PipelineError> {
+        Ok(Filter {
+            expression: Expression::parse(source)?,
+        })
+    }
+
+    /// Columns the filter reads, in order of first use.
+    pub fn columns(&self) -> Vec<String> {
+        self.expression.columns()
+    }
+
+    /// Compiles the filter to a polars predicate, checking that every column
+    /// exists in `schema` and is compared with a value of a matching type.
+    pub fn to_expr(&self, schema: &Schema) -> Result<Expr, PipelineError> {
+        match self.expression.compile(schema)? {
+            (predicate, ValueKind::Boolean) => Ok(predicate),
+            (_, kind) => Err(PipelineError::Expression(format!(
+                "the filter '{}' evaluates to {} instead of true or false",
+                self.expression,
+                kind.name()
+            ))),
+        }
+    }
+
+    /// Keeps the rows of `df` the filter matches.
+    pub fn apply(&self, df: DataFrame) -> Result<DataFrame, PipelineError> {
+        let predicate = self.to_expr(&df.schema())?;
+        Ok(df.lazy().filter(predicate).collect()?)
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.expression)
+    }
+}
+
+impl FromStr for Filter {
+    type Err = PipelineError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        Filter::parse(source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame() -> DataFrame {
+        df!(
+            "country" => [Some("DE"), Some("FR"), Some("DE"), None],
+            "value" => [Some(3.0), Some(-1.0), None, Some(5.0)]
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn apply_keeps_the_matching_rows() {
+        let kept = |source: &str| {
+            Filter::parse(source)
+                .unwrap()
+                .apply(frame())
+                .unwrap()
+                .height()
+        };
+
+        assert_eq!(kept(r#"country == "DE""#), 2);
+        assert_eq!(kept(r#"country == "DE" && value > 0"#), 1);
+        assert_eq!(kept("value > 0 || country == null"), 2);
+        assert_eq!(kept("value == null"), 1);
+        assert_eq!(kept("!(value < 0)"), 2);
+    }
+
+    #[test]
+    fn columns_are_listed_in_order_of_first_use() {
+        let filter = Filter::parse("value > 0 && (country == \"DE\" || value < -5)").unwrap();
+        assert_eq!(filter.columns(), ["value", "country"]);
+    }
+
+    #[test]
+    fn filters_must_be_true_or_false() {
+        let schema = frame().schema();
+        match Filter::parse("value * 2").unwrap().to_expr(&schema) {
+            Err(PipelineError::Expression(message)) => assert_eq!(
+                message,
+                "the filter 'value * 2' evaluates to a number instead of true or false"
+            ),
+            other => panic!("expected an expression error, got {:?}", other),
+        }
+        assert!(matches!(
+            Filter::parse("price > 1").unwrap().to_expr(&schema),
+            Err(PipelineError::MissingColumn(column)) if column == "price"
+        ));
+        assert!(Filter::parse("value >").is_err());
+    }
+}
@@ -0,0 +1,220 @@
+// src/input.rs
+
+use crate::arrow_ipc::{for_each_ipc_batch, read_ipc, read_ipc_schema, write_ipc, IpcLayout};
+use crate::error::PipelineError;
+use clap::ValueEnum;
+use polars::io::mmap::MmapBytesReader;
+use polars::prelude::*;
+use serde::Deserialize;
+use std::fs::{self, File};
+use std::path::Path;
+
+/// File formats the pipeline can read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum InputFormat {
+    Csv,
+    Parquet,
+    /// Arrow IPC file format (Feather v2)
+    Ipc,
+    /// Arrow IPC streaming format
+    IpcStream,
+}
+
+impl InputFormat {
+    pub fn name(&self) -> &'static str {
+        match self {
+            InputFormat::Csv => "CSV",
+            InputFormat::Parquet => "Parquet",
+            InputFormat::Ipc => "Arrow IPC",
+            InputFormat::IpcStream => "Arrow IPC stream",
+        }
+    }
+
+    /// Picks the format from the file extension, falling back to CSV.
+    pub fn from_path(file_path: &str) -> InputFormat {
+        let extension = Path::new(file_path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("parquet") | Some("pq") => InputFormat::Parquet,
+            Some("arrow") | Some("feather") | Some("ipc") => InputFormat::Ipc,
+            Some("arrows") => InputFormat::IpcStream,
+            _ => InputFormat::Csv,
+        }
+    }
+}
+
+/// Reads the schema of the input without loading its rows.
+///
+/// CSV types are inferred from every row, as `load_frame` does, so that the
+/// chunked reader agrees with it on columns that only turn float or text
+/// late in the file.
+pub fn read_schema(file_path: &str, format: InputFormat) -> Result<Schema, PipelineError> {
+    match format {
+        InputFormat::Csv => Ok(LazyCsvReader::new(file_path)
+            .has_header(true)
+            .with_infer_schema_length(None)
+            .finish()?
+            .schema()?
+            .as_ref()
+            .clone()),
+        InputFormat::Parquet => Ok(ParquetReader::new(open_file(file_path)?).schema()?),
+        InputFormat::Ipc => read_ipc_schema(file_path, IpcLayout::File),
+        InputFormat::IpcStream => read_ipc_schema(file_path, IpcLayout::Stream),
+    }
+}
+
+/// Loads the input into a DataFrame.
+///
+/// When `columns` is given only those columns are read: the CSV reader skips
+/// parsing the others, while the Parquet and IPC readers never decode them.
+pub fn load_frame(
+    file_path: &str,
+    format: InputFormat,
+    columns: Option<Vec<String>>,
+) -> Result<DataFrame, PipelineError> {
+    let file = open_file(file_path)?;
+
+    // Fail on unknown columns before the projected read reports a schema error
+    if let Some(columns) = &columns {
+        let schema = read_schema(file_path, format)?;
+        if let Some(missing) = columns.iter().find(|column| schema.get(column).is_none()) {
+            return Err(PipelineError::MissingColumn(missing.clone()));
+        }
+    }
+
+    let df = match format {
+        InputFormat::Csv => CsvReader::new(file)
+            .infer_schema(None)
+            .has_header(true)
+            .with_columns(columns)
+            .finish()?,
+        InputFormat::Parquet => ParquetReader::new(file).with_columns(columns).finish()?,
+        InputFormat::Ipc => read_ipc(file_path, IpcLayout::File, columns)?,
+        InputFormat::IpcStream => read_ipc(file_path, IpcLayout::Stream, columns)?,
+    };
+
+    Ok(df)
+}
+
+/// Scans the input as a LazyFrame, so that a query only reads the columns and
+/// rows it needs. CSV column types are inferred from every row, as in
+/// `load_frame`. IPC input is loaded in full with the arrow crate, since
+/// polars is built without its own IPC reader.
+pub fn scan_frame(file_path: &str, format: InputFormat) -> Result<LazyFrame, PipelineError> {
+    // Report missing files with their path, as `load_frame` does
+    open_file(file_path)?;
+
+    let lf = match format {
+        InputFormat::Csv => LazyCsvReader::new(file_path)
+            .has_header(true)
+            .with_infer_schema_length(None)
+            .finish()?,
+        InputFormat::Parquet => LazyFrame::scan_parquet(file_path, ScanArgsParquet::default())?,
+        InputFormat::Ipc => read_ipc(file_path, IpcLayout::File, None)?.lazy(),
+        InputFormat::IpcStream => read_ipc(file_path, IpcLayout::Stream, None)?.lazy(),
+    };
+
+    Ok(lf)
+}
+
+/// Reads `columns` of the input in chunks of roughly `chunk_size` rows and
+/// calls `f` with each chunk, so that memory use does not grow with the file.
+/// `schema` is the input's schema as returned by `read_schema`.
+///
+/// IPC input is read one record batch at a time whatever `chunk_size` is, as
+/// the batches were sized by the writer and are decoded whole.
+pub fn for_each_batch<F>(
+    file_path: &str,
+    format: InputFormat,
+    schema: &Schema,
+    columns: Vec<String>,
+    chunk_size: usize,
+    mut f: F,
+) -> Result<(), PipelineError>
+where
+    F: FnMut(DataFrame) -> Result<(), PipelineError>,
+{
+    let file = open_file(file_path)?;
+    let projection = columns
+        .iter()
+        .map(|column| {
+            schema
+                .index_of(column)
+                .ok_or_else(|| PipelineError::MissingColumn(column.clone()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match format {
+        InputFormat::Csv => {
+            // The read-based batched reader of polars 0.29 yields nothing for
+            // files smaller than one chunk, so memory-map the file instead
+            let reader: Box<dyn MmapBytesReader> = Box::new(file);
+            let mut batches = CsvReader::new(reader)
+                .has_header(true)
+                .with_projection(Some(projection))
+                .with_chunk_size(chunk_size)
+                .batched_mmap(Some(Arc::new(schema.clone())))?;
+            while let Some(chunks) = batches.next_batches(1)? {
+                chunks.into_iter().try_for_each(&mut f)?;
+            }
+        }
+        InputFormat::Parquet => {
+            let mut batches = ParquetReader::new(file)
+                .with_projection(Some(projection))
+                .batched(chunk_size)?;
+            while let Some(chunks) = batches.next_batches(1)? {
+                chunks.into_iter().try_for_each(&mut f)?;
+            }
+        }
+        InputFormat::Ipc => for_each_ipc_batch(file_path, IpcLayout::File, Some(columns), f)?,
+        InputFormat::IpcStream => {
+            for_each_ipc_batch(file_path, IpcLayout::Stream, Some(columns), f)?
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes a DataFrame in any of the formats the pipeline reads.
+pub fn write_frame(
+    df: &mut DataFrame,
+    file_path: &str,
+    format: InputFormat,
+) -> Result<(), PipelineError> {
+    let create = || create_file(file_path);
+
+    match format {
+        InputFormat::Csv => CsvWriter::new(create()?).has_header(true).finish(df)?,
+        InputFormat::Parquet => {
+            ParquetWriter::new(create()?).finish(df)?;
+        }
+        InputFormat::Ipc => write_ipc(df, file_path, IpcLayout::File)?,
+        InputFormat::IpcStream => write_ipc(df, file_path, IpcLayout::Stream)?,
+    }
+
+    Ok(())
+}
+
+fn open_file(file_path: &str) -> Result<File, PipelineError> {
+    File::open(file_path).map_err(|err| PipelineError::io_at(file_path, err))
+}
+
+/// Creates or truncates `file_path`, creating its directory first if needed.
+pub(crate) fn create_file(file_path: &str) -> Result<File, PipelineError> {
+    create_parent_dir(file_path)?;
+    File::create(file_path).map_err(|err| PipelineError::io_at(file_path, err))
+}
+
+/// Creates the directory `file_path` is to be written in.
+pub(crate) fn create_parent_dir(file_path: &str) -> Result<(), PipelineError> {
+    match Path::new(file_path).parent() {
+        Some(parent) => {
+            fs::create_dir_all(parent).map_err(|err| PipelineError::io_at(file_path, err))
+        }
+        None => Ok(()),
+    }
+}
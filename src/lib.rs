@@ -0,0 +1,30 @@
+// src/lib.rs
+
+//! Reads CSV, Parquet or Arrow IPC data with polars, directly or through a
+//! SQL query, computes summary statistics, correlations and principal
+//! components, draws them as SVG, PNG, JPEG or BMP charts with plotters, and
+//! writes HTML or Markdown reports of the results.
+//!
+//! `Pipeline` runs a whole analysis from a few chained options; the modules
+//! expose every stage on its own.
+
+mod arrow_ipc;
+pub mod correlation;
+pub mod data_processing;
+pub mod derive;
+pub mod error;
+pub mod expression;
+pub mod filter;
+pub mod input;
+pub mod pipeline;
+pub mod profile;
+pub mod query;
+pub mod report;
+pub mod runner;
+pub mod streaming;
+pub mod summary_file;
+pub mod theme;
+pub mod visualization;
+
+pub use error::PipelineError;
+pub use pipeline::{Pipeline, PipelineOutput, Source};
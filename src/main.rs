@@ -0,0 +1,638 @@
+// src/main.rs
+
+mod console;
+
+use clap::{Parser, Subcommand};
+use console::{print_correlations, print_matrix, print_pca, print_profile, print_summary_table};
+use data_science_pipeline::correlation::CorrelationMethod;
+use data_science_pipeline::data_processing::ColumnSelection;
+use data_science_pipeline::derive::DerivedColumn;
+use data_science_pipeline::filter::Filter;
+use data_science_pipeline::input::{load_frame, write_frame, InputFormat};
+use data_science_pipeline::profile::profile_frame;
+use data_science_pipeline::query::Table;
+use data_science_pipeline::report::parse_report_path;
+use data_science_pipeline::runner::{
+    load_config, run_pipeline, StepOutput, StepReport, StepStatus,
+};
+use data_science_pipeline::summary_file::parse_summary_path;
+use data_science_pipeline::theme::parse_theme_name;
+use data_science_pipeline::visualization::{
+    check_dpi, parse_chart_size, parse_name_template, BinRule, Chart, ChartFormat, ChartOptions,
+};
+use data_science_pipeline::{Pipeline, PipelineError};
+use std::process;
+
+/// Data Science Pipeline in Rust
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Compute summary statistics of the selected columns
+    Describe(DescribeArgs),
+    /// Draw charts from the input data or from a saved summary
+    Plot(PlotArgs),
+    /// Show the type, nulls, distinct and most frequent values of every column
+    Profile(ProfileArgs),
+    /// Check that the input can be read and the selected columns analyzed
+    Validate(ValidateArgs),
+    /// Convert the input to CSV, Parquet or Arrow IPC
+    Convert(ConvertArgs),
+    /// Correlate the selected columns and run a principal component analysis
+    Compare(CompareArgs),
+    /// Run the steps declared in a TOML or YAML pipeline file
+    Run(RunArgs),
+    /// Summarize and optionally plot the result of a SQL query over the input files
+    Query(QueryArgs),
+}
+
+/// Options shared by every subcommand that reads a data file.
+#[derive(clap::Args, Debug)]
+struct InputArgs {
+    /// Path to the CSV, Parquet or Arrow IPC data file
+    #[arg(short, long, default_value = "data/large_dataset.csv")]
+    input: String,
+
+    /// Format of the input file (detected from the extension when omitted)
+    #[arg(long, value_enum)]
+    input_format: Option<InputFormat>,
+}
+
+impl InputArgs {
+    fn format(&self) -> InputFormat {
+        self.input_format
+            .unwrap_or_else(|| InputFormat::from_path(&self.input))
+    }
+
+    fn pipeline(&self) -> Pipeline {
+        Pipeline::new(&self.input).format(self.format())
+    }
+}
+
+/// Column selection shared by the subcommands that analyze columns.
+#[derive(clap::Args, Debug)]
+struct ColumnArgs {
+    /// Column names to analyze (repeat the flag or separate with commas)
+    #[arg(short, long, value_delimiter = ',', conflicts_with = "all_numeric")]
+    column: Vec<String>,
+
+    /// Analyze every numeric column in the file (the default without --column)
+    #[arg(long)]
+    all_numeric: bool,
+
+    /// Add a computed column before analyzing, e.g. `log_value = log(value)` (repeatable)
+    #[arg(long = "derive", value_name = "NAME=EXPRESSION")]
+    derived: Vec<DerivedColumn>,
+
+    /// Only analyze the rows matching this expression, e.g. `country == "DE" && value > 0`
+    #[arg(long)]
+    filter: Option<Filter>,
+}
+
+impl ColumnArgs {
+    fn selection(&self) -> ColumnSelection {
+        if self.column.is_empty() {
+            ColumnSelection::AllNumeric
+        } else {
+            ColumnSelection::Named(self.column.clone())
+        }
+    }
+
+    fn configure(&self, pipeline: Pipeline) -> Pipeline {
+        let pipeline = self
+            .derived
+            .iter()
+            .fold(pipeline.select(self.selection()), |pipeline, column| {
+                pipeline.derive(column.clone())
+            });
+        match &self.filter {
+            Some(filter) => pipeline.filter(filter.clone()),
+            None => pipeline,
+        }
+    }
+}
+
+/// Chart file options shared by the subcommands that draw charts.
+#[derive(clap::Args, Debug)]
+struct ChartArgs {
+    /// File format of the charts
+    #[arg(long, value_enum, default_value = "svg")]
+    chart_format: ChartFormat,
+
+    /// Chart size in points of 1/96 inch, e.g. `1200x900` (default: the theme's size or 800x600)
+    #[arg(long, value_name = "WIDTHxHEIGHT", value_parser = parse_chart_size)]
+    chart_size: Option<(u32, u32)>,
+
+    /// Resolution of PNG, JPEG and BMP charts; 192 doubles the pixels of the default 96
+    #[arg(long, default_value_t = 96, value_parser = parse_dpi)]
+    dpi: u32,
+
+    /// Directory the charts are saved in, created when missing
+    #[arg(long, default_value = "output")]
+    output_dir: String,
+
+    /// Chart file names from {dataset}, {column}, {chart} and {timestamp}, e.g. `{dataset}_{column}_{chart}_{timestamp}`
+    #[arg(long, default_value = "{column}_{chart}", value_parser = parse_name_template)]
+    name_template: String,
+
+    /// Chart colors and fonts: `light`, `dark`, `print`, `colorblind` or a .toml/.yaml theme file
+    #[arg(long, default_value = "light", value_parser = parse_theme_name)]
+    theme: String,
+}
+
+impl ChartArgs {
+    fn options(&self) -> Result<ChartOptions, PipelineError> {
+        ChartOptions::new(
+            &self.theme,
+            self.chart_format,
+            self.chart_size,
+            self.dpi,
+            &self.output_dir,
+            &self.name_template,
+        )
+    }
+}
+
+fn parse_dpi(value: &str) -> Result<u32, String> {
+    let dpi = value
+        .parse::<u32>()
+        .map_err(|_| format!("expected a resolution in dpi, got '{}'", value))?;
+    check_dpi(dpi)
+}
+
+fn parse_chunk_size(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(rows) if rows > 0 => Ok(rows),
+        _ => Err(format!(
+            "expected a positive number of rows, got '{}'",
+            value
+        )),
+    }
+}
+
+#[derive(clap::Args, Debug)]
+struct DescribeArgs {
+    #[command(flatten)]
+    input: InputArgs,
+
+    #[command(flatten)]
+    columns: ColumnArgs,
+
+    /// Summarize the columns separately for every value of this column
+    #[arg(long, conflicts_with = "streaming")]
+    group_by: Option<String>,
+
+    /// Compute statistics chunk by chunk with bounded memory (quartiles are estimated)
+    #[arg(long)]
+    streaming: bool,
+
+    /// Rows per chunk in streaming mode; Arrow IPC input is read one record batch at a time instead
+    #[arg(long, default_value_t = 100_000, requires = "streaming", value_parser = parse_chunk_size)]
+    chunk_size: usize,
+
+    /// Save the statistics as JSON, CSV or Parquet, picked by the file extension
+    #[arg(long, value_parser = parse_summary_path)]
+    summary_out: Option<String>,
+
+    /// Also write an HTML or Markdown report, picked by the file extension
+    #[arg(long, value_parser = parse_report_path)]
+    report: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+struct PlotArgs {
+    #[command(flatten)]
+    input: InputArgs,
+
+    #[command(flatten)]
+    columns: ColumnArgs,
+
+    /// Summarize the columns separately for every value of this column
+    #[arg(long)]
+    group_by: Option<String>,
+
+    /// Draw the summary charts from statistics saved by `describe --summary-out`
+    #[arg(
+        long,
+        value_parser = parse_summary_path,
+        conflicts_with_all = ["column", "all_numeric", "derived", "filter", "group_by"]
+    )]
+    summary: Option<String>,
+
+    /// Histogram bins: a fixed count or one of `sturges`, `fd` (Freedman–Diaconis), `scott`
+    #[arg(long, default_value = "sturges", conflicts_with = "summary")]
+    bins: BinRule,
+
+    #[command(flatten)]
+    chart: ChartArgs,
+
+    /// Also write an HTML or Markdown report, picked by the file extension
+    #[arg(long, value_parser = parse_report_path)]
+    report: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+struct ProfileArgs {
+    #[command(flatten)]
+    input: InputArgs,
+
+    /// Number of most frequent values to show per column
+    #[arg(long, default_value_t = 5)]
+    top: usize,
+}
+
+#[derive(clap::Args, Debug)]
+struct ValidateArgs {
+    #[command(flatten)]
+    input: InputArgs,
+
+    #[command(flatten)]
+    columns: ColumnArgs,
+}
+
+#[derive(clap::Args, Debug)]
+struct ConvertArgs {
+    #[command(flatten)]
+    input: InputArgs,
+
+    /// Columns to keep (repeat the flag or separate with commas), all when omitted
+    #[arg(short, long, value_delimiter = ',')]
+    column: Vec<String>,
+
+    /// Path of the converted file
+    #[arg(short, long)]
+    output: String,
+
+    /// Format of the converted file (detected from the extension when omitted)
+    #[arg(long, value_enum)]
+    output_format: Option<InputFormat>,
+}
+
+#[derive(clap::Args, Debug)]
+struct CompareArgs {
+    #[command(flatten)]
+    input: InputArgs,
+
+    #[command(flatten)]
+    columns: ColumnArgs,
+
+    /// Correlation methods: `pearson`, `spearman` and/or `kendall`
+    #[arg(long, value_enum, value_delimiter = ',', default_value = "pearson")]
+    correlation: Vec<CorrelationMethod>,
+
+    /// Also print the covariance matrix and run a principal component analysis
+    #[arg(long)]
+    pca: bool,
+
+    /// Number of components to project the rows onto (the scatter plots the first two)
+    #[arg(long, default_value_t = 2, requires = "pca")]
+    pca_components: usize,
+
+    #[command(flatten)]
+    chart: ChartArgs,
+
+    /// Also write an HTML or Markdown report, picked by the file extension
+    #[arg(long, value_parser = parse_report_path)]
+    report: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+struct QueryArgs {
+    /// SQL statement to run, e.g. `SELECT region, SUM(sales) AS sales FROM orders GROUP BY region`
+    sql: String,
+
+    /// Data file to query, as `name=path` or a path named after its file stem (repeatable)
+    #[arg(short, long = "table", value_name = "NAME=PATH", required = true)]
+    tables: Vec<Table>,
+
+    #[command(flatten)]
+    columns: ColumnArgs,
+
+    /// Summarize the result separately for every value of this column
+    #[arg(long)]
+    group_by: Option<String>,
+
+    /// Also draw the summary, histogram, box and violin charts of the result
+    #[arg(long)]
+    plot: bool,
+
+    /// Histogram bins: a fixed count or one of `sturges`, `fd` (Freedman–Diaconis), `scott`
+    #[arg(long, default_value = "sturges", requires = "plot")]
+    bins: BinRule,
+
+    #[command(flatten)]
+    chart: ChartArgs,
+
+    /// Save the statistics as JSON, CSV or Parquet, picked by the file extension
+    #[arg(long, value_parser = parse_summary_path)]
+    summary_out: Option<String>,
+
+    /// Also write an HTML or Markdown report, picked by the file extension
+    #[arg(long, value_parser = parse_report_path)]
+    report: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+struct RunArgs {
+    /// Path to the pipeline definition (.toml, .yaml or .yml)
+    config: String,
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    let result = match cli.command {
+        Command::Describe(args) => describe(args),
+        Command::Plot(args) => plot(args),
+        Command::Profile(args) => profile(args),
+        Command::Validate(args) => validate(args),
+        Command::Convert(args) => convert(args),
+        Command::Compare(args) => compare(args),
+        Command::Run(args) => run(args),
+        Command::Query(args) => query(args),
+    };
+
+    if let Err(err) = result {
+        eprintln!("Error: {}", err);
+        process::exit(err.exit_code());
+    }
+}
+
+fn describe(args: DescribeArgs) -> Result<(), PipelineError> {
+    let mut pipeline = args.columns.configure(args.input.pipeline());
+    if let Some(group_column) = &args.group_by {
+        pipeline = pipeline.group_by(group_column);
+    }
+    // Streaming mode keeps no rows around
+    if args.streaming {
+        pipeline = pipeline.streaming(args.chunk_size);
+    }
+    if let Some(output_path) = &args.summary_out {
+        pipeline = pipeline.summary_out(output_path);
+    }
+    if let Some(report_path) = &args.report {
+        pipeline = pipeline.report(report_path);
+    }
+
+    let output = pipeline.run()?;
+    print_summary_table(&output.summaries);
+    if let Some(output_path) = &args.summary_out {
+        println!("Summary statistics written to {}", output_path);
+    }
+    print_report_path(args.report.as_deref());
+
+    Ok(())
+}
+
+fn plot(args: PlotArgs) -> Result<(), PipelineError> {
+    // A saved summary has no rows, so only the summary charts can be drawn
+    let mut pipeline = match &args.summary {
+        Some(summary_path) => Pipeline::from_summary(summary_path).charts(&[Chart::Summary]),
+        None => args
+            .columns
+            .configure(args.input.pipeline())
+            .charts(&Chart::all())
+            .bins(args.bins),
+    }
+    .chart_options(args.chart.options()?);
+    if let Some(group_column) = &args.group_by {
+        pipeline = pipeline.group_by(group_column);
+    }
+    if let Some(report_path) = &args.report {
+        pipeline = pipeline.report(report_path);
+    }
+    let output = pipeline.run()?;
+    print_chart_paths(&output.charts);
+
+    if args.summary.is_some() {
+        println!("Visualization completed successfully.");
+    } else {
+        println!("Data processing and visualization completed successfully.");
+    }
+    print_report_path(args.report.as_deref());
+    Ok(())
+}
+
+fn profile(args: ProfileArgs) -> Result<(), PipelineError> {
+    let df = load_frame(&args.input.input, args.input.format(), None)?;
+
+    println!(
+        "'{}': {} rows, {} columns",
+        args.input.input,
+        df.height(),
+        df.width()
+    );
+    println!();
+    print_profile(&profile_frame(&df, args.top)?);
+
+    Ok(())
+}
+
+fn validate(args: ValidateArgs) -> Result<(), PipelineError> {
+    // Summarizing surfaces every problem the other subcommands would hit
+    let output = args.columns.configure(args.input.pipeline()).run()?;
+
+    let columns: Vec<&str> = output
+        .summaries
+        .iter()
+        .map(|summary| summary.column.as_str())
+        .collect();
+    println!(
+        "'{}' is valid: {} rows, {} columns can be analyzed ({})",
+        args.input.input,
+        output.frame.as_ref().map_or(0, |frame| frame.height()),
+        columns.len(),
+        columns.join(", ")
+    );
+
+    Ok(())
+}
+
+fn convert(args: ConvertArgs) -> Result<(), PipelineError> {
+    let columns = if args.column.is_empty() {
+        None
+    } else {
+        Some(args.column)
+    };
+    let mut df = load_frame(&args.input.input, args.input.format(), columns)?;
+
+    let format = args
+        .output_format
+        .unwrap_or_else(|| InputFormat::from_path(&args.output));
+    write_frame(&mut df, &args.output, format)?;
+
+    println!(
+        "Wrote {} rows and {} columns to {}",
+        df.height(),
+        df.width(),
+        args.output
+    );
+    Ok(())
+}
+
+fn compare(args: CompareArgs) -> Result<(), PipelineError> {
+    let mut pipeline = args
+        .columns
+        .configure(args.input.pipeline())
+        .chart_options(args.chart.options()?);
+    for method in &args.correlation {
+        pipeline = pipeline.correlation(*method);
+    }
+    if args.pca {
+        pipeline = pipeline.pca(args.pca_components);
+    }
+    if let Some(report_path) = &args.report {
+        pipeline = pipeline.report(report_path);
+    }
+    let output = pipeline.run()?;
+    print_chart_paths(&output.charts);
+
+    for matrix in &output.correlations {
+        print_correlations(matrix);
+    }
+    if let (Some(covariance), Some(pca)) = (&output.covariance, &output.pca) {
+        print_matrix("Covariance", &pca.columns, &pca.columns, covariance);
+        print_pca(pca);
+    }
+    print_report_path(args.report.as_deref());
+
+    Ok(())
+}
+
+fn run(args: RunArgs) -> Result<(), PipelineError> {
+    let config = load_config(&args.config)?;
+    let reports = run_pipeline(&config, print_step);
+
+    let name_width = reports
+        .iter()
+        .map(|report| report.name.len())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    println!();
+    println!(
+        "{:<name_width$} | {:<9} | {:<9} | {:>10}",
+        "Step",
+        "Type",
+        "Status",
+        "Time",
+        name_width = name_width
+    );
+    println!(
+        "{}-|-{}-|-{}-|-{}",
+        "-".repeat(name_width),
+        "-".repeat(9),
+        "-".repeat(9),
+        "-".repeat(10)
+    );
+    for report in &reports {
+        let status = match report.status {
+            StepStatus::Succeeded(_) => "ok",
+            StepStatus::Failed(_) => "failed",
+            StepStatus::Skipped => "skipped",
+        };
+        println!(
+            "{:<name_width$} | {:<9} | {:<9} | {:>8.3} s",
+            report.name,
+            report.kind,
+            status,
+            report.duration.as_secs_f64(),
+            name_width = name_width
+        );
+    }
+    println!();
+
+    // Exit with the code of the step that stopped the pipeline
+    match reports.into_iter().find_map(|report| match report.status {
+        StepStatus::Failed(err) => Some(err),
+        _ => None,
+    }) {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+// Shows what a pipeline file step did as soon as it is over
+fn print_step(report: &StepReport) {
+    let output = match &report.status {
+        StepStatus::Succeeded(output) => output,
+        StepStatus::Skipped => return,
+        // The error is shown once the pipeline has stopped
+        StepStatus::Failed(_) => {
+            println!("==> {} ({})", report.name, report.kind);
+            return;
+        }
+    };
+
+    println!("==> {} ({})", report.name, report.kind);
+    match output {
+        StepOutput::Loaded { rows, columns } => {
+            println!("Loaded {} rows and {} columns", rows, columns)
+        }
+        StepOutput::Filtered { kept, rows } => println!("Kept {} of {} rows", kept, rows),
+        StepOutput::Derived { column } => println!("Derived column '{}'", column),
+        StepOutput::Summarized { summaries, output } => {
+            print_summary_table(summaries);
+            if let Some(output_path) = output {
+                println!("Summary statistics written to {}", output_path);
+            }
+        }
+        StepOutput::Plotted(charts) => print_chart_paths(charts),
+    }
+}
+
+fn query(args: QueryArgs) -> Result<(), PipelineError> {
+    let mut pipeline = args
+        .columns
+        .configure(Pipeline::query(&args.tables, &args.sql))
+        .chart_options(args.chart.options()?);
+    if let Some(group_column) = &args.group_by {
+        pipeline = pipeline.group_by(group_column);
+    }
+    if args.plot {
+        pipeline = pipeline.charts(&Chart::all()).bins(args.bins);
+    }
+    if let Some(output_path) = &args.summary_out {
+        pipeline = pipeline.summary_out(output_path);
+    }
+    if let Some(report_path) = &args.report {
+        pipeline = pipeline.report(report_path);
+    }
+
+    let output = pipeline.run()?;
+    if let Some(frame) = &output.frame {
+        println!(
+            "Query returned {} rows and {} columns",
+            frame.height(),
+            frame.width()
+        );
+    }
+    print_summary_table(&output.summaries);
+    if let Some(output_path) = &args.summary_out {
+        println!("Summary statistics written to {}", output_path);
+    }
+    if args.plot {
+        print_chart_paths(&output.charts);
+        println!("Visualization completed successfully.");
+    }
+    print_report_path(args.report.as_deref());
+
+    Ok(())
+}
+
+fn print_chart_paths(charts: &[String]) {
+    for chart in charts {
+        println!("Chart saved to {}", chart);
+    }
+}
+
+fn print_report_path(report_path: Option<&str>) {
+    if let Some(report_path) = report_path {
+        println!("Report written to {}", report_path);
+    }
+}
@@ -0,0 +1,498 @@
+// src/pipeline.rs
+
+use crate::correlation::{correlation_matrix, CorrelationMatrix, CorrelationMethod};
+use crate::data_processing::{
+    analyze_frame, covariance_matrix, principal_components, process_data, ColumnSelection,
+    DataSummary, Pca,
+};
+use crate::derive::DerivedColumn;
+use crate::error::PipelineError;
+use crate::filter::Filter;
+use crate::input::InputFormat;
+use crate::query::{run_query, Table};
+use crate::report::{write_report, ReportContext};
+use crate::streaming::process_data_streaming;
+use crate::summary_file::{read_summaries, write_summaries};
+use crate::visualization::{
+    create_correlation_heatmap, create_pca_scatter, create_scree_plot, draw_charts, BinRule, Chart,
+    ChartFormat, ChartOptions,
+};
+use polars::prelude::DataFrame;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+/// Where a pipeline reads from.
+#[derive(Clone, Debug)]
+pub enum Source {
+    /// A CSV, Parquet or Arrow IPC data file
+    Data { path: String, format: InputFormat },
+    /// Statistics saved by `write_summaries`, which hold no rows
+    Summary(String),
+    /// The result of a SQL statement over data files registered as tables
+    Query { tables: Vec<Table>, sql: String },
+}
+
+/// One analysis from source to outputs: which columns to summarize, which
+/// statistics to add, which charts to draw and where to save the summary.
+///
+/// Options are set with chained calls and nothing is read until `run`.
+#[derive(Clone, Debug)]
+pub struct Pipeline {
+    source: Source,
+    selection: ColumnSelection,
+    derived: Vec<DerivedColumn>,
+    filter: Option<Filter>,
+    group_by: Option<String>,
+    chunk_size: Option<usize>,
+    correlations: Vec<CorrelationMethod>,
+    pca_components: Option<usize>,
+    charts: Vec<Chart>,
+    bins: BinRule,
+    chart_options: ChartOptions,
+    summary_out: Option<String>,
+    report: Option<String>,
+}
+
+/// Everything a pipeline run computed.
+#[derive(Debug)]
+pub struct PipelineOutput {
+    pub summaries: Vec<DataSummary>,
+    /// The analyzed columns, absent in streaming mode and for saved summaries
+    pub frame: Option<DataFrame>,
+    pub correlations: Vec<CorrelationMatrix>,
+    pub covariance: Option<Vec<Vec<f64>>>,
+    pub pca: Option<Pca>,
+    /// Paths of the chart files written, in the order they were drawn
+    pub charts: Vec<String>,
+}
+
+impl Pipeline {
+    /// Analyzes every numeric column of a data file, in the format given by
+    /// its extension.
+    pub fn new(path: impl Into<String>) -> Self {
+        let path = path.into();
+        let format = InputFormat::from_path(&path);
+        Pipeline::with_source(Source::Data { path, format })
+    }
+
+    /// Starts from statistics saved by `write_summaries`, so that only the
+    /// summary charts can be drawn.
+    pub fn from_summary(path: impl Into<String>) -> Self {
+        Pipeline::with_source(Source::Summary(path.into()))
+    }
+
+    /// Analyzes the result of `sql`, which can read every file in `tables`
+    /// by its table name.
+    pub fn query(tables: &[Table], sql: impl Into<String>) -> Self {
+        Pipeline::with_source(Source::Query {
+            tables: tables.to_vec(),
+            sql: sql.into(),
+        })
+    }
+
+    fn with_source(source: Source) -> Self {
+        Pipeline {
+            source,
+            selection: ColumnSelection::AllNumeric,
+            derived: Vec::new(),
+            filter: None,
+            group_by: None,
+            chunk_size: None,
+            correlations: Vec::new(),
+            pca_components: None,
+            charts: Vec::new(),
+            bins: BinRule::Sturges,
+            chart_options: ChartOptions::default(),
+            summary_out: None,
+            report: None,
+        }
+    }
+
+    /// Overrides the format detected from the file extension.
+    pub fn format(mut self, format: InputFormat) -> Self {
+        if let Source::Data {
+            format: current, ..
+        } = &mut self.source
+        {
+            *current = format;
+        }
+        self
+    }
+
+    pub fn select(mut self, selection: ColumnSelection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// Adds a column to analyze, instead of every numeric column.
+    pub fn column(mut self, column: impl Into<String>) -> Self {
+        match &mut self.selection {
+            ColumnSelection::Named(columns) => columns.push(column.into()),
+            ColumnSelection::AllNumeric => {
+                self.selection = ColumnSelection::Named(vec![column.into()])
+            }
+        }
+        self
+    }
+
+    /// Adds a computed column before anything is summarized or filtered.
+    /// Derived columns are added in order, so each can use the earlier ones.
+    pub fn derive(mut self, column: DerivedColumn) -> Self {
+        self.derived.push(column);
+        self
+    }
+
+    /// Only analyzes the rows matching `filter`.
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Summarizes the columns separately for every value of `column`.
+    pub fn group_by(mut self, column: impl Into<String>) -> Self {
+        self.group_by = Some(column.into());
+        self
+    }
+
+    /// Reads the input in chunks of `chunk_size` rows with bounded memory, or
+    /// one record batch at a time for Arrow IPC input. No rows are kept, so
+    /// only the summary charts can be drawn.
+    pub fn streaming(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = Some(chunk_size);
+        self
+    }
+
+    /// Correlates the analyzed columns and draws a heatmap of the result.
+    pub fn correlation(mut self, method: CorrelationMethod) -> Self {
+        if !self.correlations.contains(&method) {
+            self.correlations.push(method);
+        }
+        self
+    }
+
+    /// Computes the covariance matrix and principal components, projecting
+    /// the rows onto the first `components`, and draws the scree plot and
+    /// the scatter of the first two components.
+    pub fn pca(mut self, components: usize) -> Self {
+        self.pca_components = Some(components);
+        self
+    }
+
+    pub fn charts(mut self, charts: &[Chart]) -> Self {
+        self.charts = charts.to_vec();
+        self
+    }
+
+    pub fn bins(mut self, bins: BinRule) -> Self {
+        self.bins = bins;
+        self
+    }
+
+    /// Sets the file format, size and resolution of every chart.
+    pub fn chart_options(mut self, options: ChartOptions) -> Self {
+        self.chart_options = options;
+        self
+    }
+
+    /// Saves the summaries as JSON, CSV or Parquet, picked by the extension.
+    pub fn summary_out(mut self, path: impl Into<String>) -> Self {
+        self.summary_out = Some(path.into());
+        self
+    }
+
+    /// Writes the statistics, charts, input details and options of the run
+    /// to a single report, in the format picked by the extension.
+    pub fn report(mut self, path: impl Into<String>) -> Self {
+        self.report = Some(path.into());
+        self
+    }
+
+    pub fn run(&self) -> Result<PipelineOutput, PipelineError> {
+        self.check()?;
+
+        let (summaries, frame) = match &self.source {
+            Source::Summary(path) => (read_summaries(path)?, None),
+            Source::Data { path, format } => match self.chunk_size {
+                Some(chunk_size) => (
+                    process_data_streaming(
+                        path,
+                        *format,
+                        &self.selection,
+                        &self.derived,
+                        self.filter.as_ref(),
+                        chunk_size,
+                    )?,
+                    None,
+                ),
+                None => {
+                    let analysis = process_data(
+                        path,
+                        *format,
+                        &self.selection,
+                        self.group_by.as_deref(),
+                        &self.derived,
+                        self.filter.as_ref(),
+                    )?;
+                    (analysis.summaries, Some(analysis.frame))
+                }
+            },
+            Source::Query { tables, sql } => {
+                let analysis = analyze_frame(
+                    run_query(tables, sql)?,
+                    &self.selection,
+                    self.group_by.as_deref(),
+                    &self.derived,
+                    self.filter.as_ref(),
+                )?;
+                (analysis.summaries, Some(analysis.frame))
+            }
+        };
+
+        if let Some(output_path) = &self.summary_out {
+            write_summaries(&summaries, output_path)?;
+        }
+
+        // Ensure the output directory exists
+        let chart_options = self.chart_options_for_run();
+        if !self.charts.is_empty() || !self.correlations.is_empty() || self.pca_components.is_some()
+        {
+            fs::create_dir_all(&chart_options.output_dir)
+                .map_err(|err| PipelineError::io_at(&chart_options.output_dir, err))?;
+        }
+        let mut charts = draw_charts(
+            &self.charts,
+            frame.as_ref(),
+            &summaries,
+            self.bins,
+            &chart_options,
+        )?;
+
+        // Grouped summaries repeat their column once per group
+        let mut columns: Vec<String> = summaries
+            .iter()
+            .map(|summary| summary.column.clone())
+            .collect();
+        columns.dedup();
+
+        // check() made sure the correlations and PCA only run with rows
+        let mut correlations = Vec::new();
+        let mut covariance = None;
+        let mut pca = None;
+        if let Some(rows) = &frame {
+            for method in &self.correlations {
+                let matrix = correlation_matrix(rows, &columns, *method)?;
+                charts.push(create_correlation_heatmap(&matrix, &chart_options)?);
+                correlations.push(matrix);
+            }
+
+            if let Some(components) = self.pca_components {
+                let principal = principal_components(rows, &columns, Some(components))?;
+                charts.push(create_scree_plot(&principal, &chart_options)?);
+                if components >= 2 && columns.len() >= 2 {
+                    charts.push(create_pca_scatter(&principal, &chart_options)?);
+                }
+                covariance = Some(covariance_matrix(rows, &columns)?);
+                pca = Some(principal);
+            }
+        }
+
+        let output = PipelineOutput {
+            summaries,
+            frame,
+            correlations,
+            covariance,
+            pca,
+            charts,
+        };
+        if let Some(report_path) = &self.report {
+            write_report(
+                report_path,
+                &self.report_context(&output),
+                &output,
+                &chart_options,
+            )?;
+        }
+
+        Ok(output)
+    }
+
+    // The chart options with `{dataset}` filled from the source unless the
+    // caller named it
+    fn chart_options_for_run(&self) -> ChartOptions {
+        let mut options = self.chart_options.clone();
+        if options.dataset.is_empty() {
+            options.dataset = match &self.source {
+                Source::Data { path, .. } | Source::Summary(path) => Path::new(path)
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .unwrap_or_default()
+                    .to_string(),
+                Source::Query { .. } => "query".to_string(),
+            };
+        }
+        options
+    }
+
+    // The input details and the options that were set, in builder order
+    fn report_context(&self, output: &PipelineOutput) -> ReportContext {
+        let mut input = Vec::new();
+        let mut parameters = Vec::new();
+        let add = |list: &mut Vec<(String, String)>, key: &str, value: String| {
+            list.push((key.to_string(), value))
+        };
+
+        let title = match &self.source {
+            Source::Data { path, format } => {
+                add(&mut input, "File", path.clone());
+                add(&mut input, "Format", format.name().to_string());
+                if let Ok(metadata) = fs::metadata(path) {
+                    add(&mut input, "Size", format!("{} bytes", metadata.len()));
+                }
+                format!("Report for {}", path)
+            }
+            Source::Summary(path) => {
+                add(&mut input, "Summary file", path.clone());
+                format!("Report for {}", path)
+            }
+            Source::Query { tables, sql } => {
+                for table in tables {
+                    add(
+                        &mut input,
+                        &format!("Table {}", table.name),
+                        format!("{} ({})", table.path, table.format.name()),
+                    );
+                }
+                add(&mut input, "Query", sql.clone());
+                "Query report".to_string()
+            }
+        };
+        if let Some(frame) = &output.frame {
+            add(&mut input, "Rows analyzed", frame.height().to_string());
+        }
+
+        let selection = match &self.selection {
+            ColumnSelection::Named(columns) => columns.join(", "),
+            ColumnSelection::AllNumeric => "all numeric".to_string(),
+        };
+        add(&mut parameters, "Columns", selection);
+        for column in &self.derived {
+            add(&mut parameters, "Derived column", column.to_string());
+        }
+        if let Some(filter) = &self.filter {
+            add(&mut parameters, "Filter", filter.to_string());
+        }
+        if let Some(group_column) = &self.group_by {
+            add(&mut parameters, "Group by", group_column.clone());
+        }
+        if let Some(chunk_size) = self.chunk_size {
+            add(
+                &mut parameters,
+                "Streaming",
+                format!("chunks of {} rows", chunk_size),
+            );
+        }
+        if !self.correlations.is_empty() {
+            let methods: Vec<&str> = self
+                .correlations
+                .iter()
+                .map(|method| method.name())
+                .collect();
+            add(&mut parameters, "Correlation", methods.join(", "));
+        }
+        if let Some(components) = self.pca_components {
+            add(&mut parameters, "PCA components", components.to_string());
+        }
+        if !self.charts.is_empty() {
+            let charts: Vec<&str> = self.charts.iter().map(|chart| chart.name()).collect();
+            add(&mut parameters, "Charts", charts.join(", "));
+        }
+        if self.charts.contains(&Chart::Histogram) {
+            add(&mut parameters, "Histogram bins", self.bins.to_string());
+        }
+        if !output.charts.is_empty() {
+            let options = &self.chart_options;
+            let mut format = format!(
+                "{}, {}x{}",
+                options.format.name(),
+                options.size.0,
+                options.size.1
+            );
+            if options.format != ChartFormat::Svg {
+                let _ = write!(format, " at {} dpi", options.dpi);
+            }
+            add(&mut parameters, "Chart format", format);
+            let files = Path::new(&options.output_dir).join(format!(
+                "{}.{}",
+                options.name_template,
+                options.format.extension()
+            ));
+            add(&mut parameters, "Chart files", files.display().to_string());
+            add(&mut parameters, "Chart theme", options.theme.name.clone());
+        }
+        if let Some(path) = &self.summary_out {
+            add(&mut parameters, "Summary saved to", path.clone());
+        }
+
+        ReportContext {
+            title,
+            input,
+            parameters,
+        }
+    }
+
+    // Reject combinations that need rows the source does not provide
+    fn check(&self) -> Result<(), PipelineError> {
+        if let Source::Summary(_) = &self.source {
+            if let Some(filter) = &self.filter {
+                return Err(PipelineError::Config(format!(
+                    "a saved summary has no rows to filter with '{}'",
+                    filter
+                )));
+            }
+            if let Some(column) = self.derived.first() {
+                return Err(PipelineError::Config(format!(
+                    "a saved summary has no rows to derive '{}' from",
+                    column
+                )));
+            }
+        }
+
+        if self.chunk_size == Some(0) {
+            return Err(PipelineError::Config(
+                "streaming mode needs at least one row per chunk".to_string(),
+            ));
+        }
+
+        // A query result is already in memory, so there is nothing to stream
+        if let (Source::Query { .. }, Some(_)) = (&self.source, self.chunk_size) {
+            return Err(PipelineError::Config(
+                "streaming mode reads a single data file, not a SQL query".to_string(),
+            ));
+        }
+
+        let without_rows = match (&self.source, self.chunk_size) {
+            (Source::Summary(_), _) => Some("a saved summary"),
+            (_, Some(_)) => Some("streaming mode"),
+            (_, None) => None,
+        };
+        let chart = self.charts.iter().find(|chart| **chart != Chart::Summary);
+        let needs_rows = if self.group_by.is_some() {
+            Some("grouping".to_string())
+        } else if !self.correlations.is_empty() {
+            Some("correlation".to_string())
+        } else if self.pca_components.is_some() {
+            Some("PCA".to_string())
+        } else {
+            chart.map(|chart| format!("the {} chart", chart.name()))
+        };
+
+        match (without_rows, needs_rows) {
+            (Some(source), Some(analysis)) => Err(PipelineError::Config(format!(
+                "{} requires the rows, which {} does not keep",
+                analysis, source
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
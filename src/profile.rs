@@ -0,0 +1,96 @@
+// src/profile.rs
+
+use crate::data_processing::{summarize_column, DataSummary};
+use crate::error::PipelineError;
+use polars::prelude::*;
+
+/// What a column holds, whatever its type.
+#[derive(Clone, Debug)]
+pub struct ColumnProfile {
+    pub column: String,
+    pub dtype: String,
+    pub null_count: usize,
+    pub null_percent: f64,
+    /// Number of distinct non-null values
+    pub distinct_count: usize,
+    /// The most frequent non-null values with their counts, most frequent first
+    pub top_values: Vec<(String, usize)>,
+    /// Shortest and longest value in characters, for text columns
+    pub lengths: Option<(usize, usize)>,
+    /// Summary statistics, for numeric columns with at least one value
+    pub summary: Option<DataSummary>,
+}
+
+/// Profiles every column of `df`, keeping the `top` most frequent values of
+/// each.
+pub fn profile_frame(df: &DataFrame, top: usize) -> Result<Vec<ColumnProfile>, PipelineError> {
+    df.get_columns()
+        .iter()
+        .map(|series| profile_column(df, series, top))
+        .collect()
+}
+
+fn profile_column(
+    df: &DataFrame,
+    series: &Series,
+    top: usize,
+) -> Result<ColumnProfile, PipelineError> {
+    let null_count = series.null_count();
+    let null_percent = if series.is_empty() {
+        0.0
+    } else {
+        null_count as f64 / series.len() as f64 * 100.0
+    };
+
+    // Count every value once, most frequent first and ties in value order
+    let mut values = series.drop_nulls();
+    values.rename("value");
+    let counts = DataFrame::new(vec![values])?
+        .lazy()
+        .groupby([col("value")])
+        .agg([count().alias("count")])
+        .sort_by_exprs([col("count"), col("value")], [true, false], false)
+        .collect()?;
+    let top_counts = counts.head(Some(top));
+    let top_values = top_counts
+        .column("value")?
+        .cast(&DataType::Utf8)?
+        .utf8()?
+        .into_iter()
+        .zip(top_counts.column("count")?.u32()?)
+        .map(|(value, count)| {
+            (
+                value.unwrap_or_default().to_string(),
+                count.unwrap_or_default() as usize,
+            )
+        })
+        .collect();
+
+    let lengths = match series.dtype() {
+        DataType::Utf8 => {
+            let lengths = series.utf8()?.str_n_chars();
+            lengths
+                .min()
+                .zip(lengths.max())
+                .map(|(min, max)| (min as usize, max as usize))
+        }
+        _ => None,
+    };
+
+    let summary = if series.dtype().is_numeric() && null_count < series.len() {
+        Some(summarize_column(df, series.name())?)
+    } else {
+        None
+    };
+
+    Ok(ColumnProfile {
+        column: series.name().to_string(),
+        dtype: series.dtype().to_string(),
+        null_count,
+        null_percent,
+        distinct_count: counts.height(),
+        top_values,
+        lengths,
+        summary,
+    })
+}
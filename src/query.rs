@@ -0,0 +1,141 @@
+// src/query.rs
+
+use crate::error::PipelineError;
+use crate::expression::is_identifier;
+use crate::input::{scan_frame, InputFormat};
+use polars::prelude::*;
+use polars::sql::SQLContext;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// A data file registered under a table name for SQL queries, written as
+/// `name=path` or just `path` to name the table after the file stem.
+#[derive(Clone, Debug)]
+pub struct Table {
+    pub name: String,
+    pub path: String,
+    pub format: InputFormat,
+}
+
+impl Table {
+    /// Registers `path` as `name`, in the format given by its extension.
+    pub fn new(name: impl Into<String>, path: impl Into<String>) -> Self {
+        let path = path.into();
+        let format = InputFormat::from_path(&path);
+        Table {
+            name: name.into(),
+            path,
+            format,
+        }
+    }
+
+    pub fn parse(definition: &str) -> Result<Table, PipelineError> {
+        let (name, path) = match definition.split_once('=') {
+            Some((name, path)) => (name.trim().to_string(), path.trim()),
+            None => {
+                let stem = Path::new(definition)
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .unwrap_or_default();
+                (stem.to_string(), definition)
+            }
+        };
+
+        if path.is_empty() {
+            return Err(PipelineError::Query(format!(
+                "no file given for table '{}'",
+                name
+            )));
+        }
+        if !is_identifier(&name) || name.contains('.') {
+            return Err(PipelineError::Query(format!(
+                "'{}' is not a valid table name, register the file as 'name={}'",
+                name, path
+            )));
+        }
+
+        Ok(Table::new(name, path))
+    }
+}
+
+impl fmt::Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.name, self.path)
+    }
+}
+
+impl FromStr for Table {
+    type Err = PipelineError;
+
+    fn from_str(definition: &str) -> Result<Self, Self::Err> {
+        Table::parse(definition)
+    }
+}
+
+/// Registers every table and runs a single SQL statement over them, returning
+/// the result as a DataFrame. CSV and Parquet tables are scanned lazily, so
+/// only the columns and rows the statement needs are read.
+pub fn run_query(tables: &[Table], sql: &str) -> Result<DataFrame, PipelineError> {
+    let mut context = SQLContext::new();
+    for (index, table) in tables.iter().enumerate() {
+        if tables[..index].iter().any(|other| other.name == table.name) {
+            return Err(PipelineError::Query(format!(
+                "the table '{}' is registered twice",
+                table.name
+            )));
+        }
+        context.register(&table.name, scan_frame(&table.path, table.format)?);
+    }
+
+    // Syntax errors and unknown tables surface while planning, missing
+    // columns only once the plan runs
+    let plan = context
+        .execute(sql)
+        .map_err(|err| PipelineError::Query(err.to_string()))?;
+    Ok(plan.collect()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_reads_scanned_csv_tables() {
+        let tables = [Table::parse("data/large_dataset.csv").unwrap()];
+        let df = run_query(
+            &tables,
+            "SELECT id, value FROM large_dataset WHERE value > 40 ORDER BY id",
+        )
+        .unwrap();
+
+        let ids: Vec<Option<i64>> = df
+            .column("id")
+            .unwrap()
+            .i64()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(ids, [Some(7), Some(9), Some(10)]);
+    }
+
+    #[test]
+    fn query_errors_name_the_problem() {
+        let missing = [Table::parse("orders=data/missing.csv").unwrap()];
+        match run_query(&missing, "SELECT * FROM orders") {
+            Err(PipelineError::Io(err)) => assert!(err.to_string().starts_with("data/missing.csv")),
+            other => panic!("expected an I/O error, got {:?}", other),
+        }
+
+        let tables = [Table::parse("data/large_dataset.csv").unwrap()];
+        assert!(matches!(
+            run_query(&tables, "SELECT * FROM orders"),
+            Err(PipelineError::Query(_))
+        ));
+        let twice = [tables[0].clone(), tables[0].clone()];
+        assert!(matches!(
+            run_query(&twice, "SELECT * FROM large_dataset"),
+            Err(PipelineError::Query(message)) if message.contains("registered twice")
+        ));
+    }
+}
@@ -0,0 +1,722 @@
+// src/report.rs
+
+use crate::correlation::CorrelationMatrix;
+use crate::data_processing::{DataSummary, Pca};
+use crate::error::PipelineError;
+use crate::input::create_parent_dir;
+use crate::pipeline::PipelineOutput;
+use crate::visualization::{sanitize_file_name, ChartFormat, ChartOptions};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+/// File formats a run report can be written in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// A single HTML page with the charts embedded, viewable offline
+    Html,
+    /// GitHub-flavoured Markdown linking to the chart files
+    Markdown,
+}
+
+impl ReportFormat {
+    /// Picks the format from the file extension.
+    pub fn from_path(file_path: &str) -> Option<ReportFormat> {
+        let extension = Path::new(file_path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("html") | Some("htm") => Some(ReportFormat::Html),
+            Some("md") | Some("markdown") => Some(ReportFormat::Markdown),
+            _ => None,
+        }
+    }
+}
+
+/// Checks a `--report` path for clap, so that an unsupported extension is
+/// reported before any data is read.
+pub fn parse_report_path(file_path: &str) -> Result<String, String> {
+    match ReportFormat::from_path(file_path) {
+        Some(_) => Ok(file_path.to_string()),
+        None => Err(format!("expected a .html or .md file, got '{}'", file_path)),
+    }
+}
+
+/// What a report tells about a run besides its results.
+#[derive(Clone, Debug, Default)]
+pub struct ReportContext {
+    pub title: String,
+    /// Facts about the input, such as its path, format and size
+    pub input: Vec<(String, String)>,
+    /// The options the run was made with
+    pub parameters: Vec<(String, String)>,
+}
+
+/// Writes the results of a run to `file_path` in the format given by its
+/// extension.
+pub fn write_report(
+    file_path: &str,
+    context: &ReportContext,
+    output: &PipelineOutput,
+    chart_options: &ChartOptions,
+) -> Result<(), PipelineError> {
+    let format = ReportFormat::from_path(file_path)
+        .ok_or_else(|| PipelineError::Parse(format!("unsupported report file '{}'", file_path)))?;
+
+    // Chart links are resolved against the report's directory
+    create_parent_dir(file_path)?;
+    let report = match format {
+        ReportFormat::Html => html_report(context, output, chart_options)?,
+        ReportFormat::Markdown => markdown_report(context, output, chart_options, file_path),
+    };
+    fs::write(file_path, report).map_err(|err| PipelineError::io_at(file_path, err))
+}
+
+const STYLE: &str =
+    "body { font-family: sans-serif; margin: 2em auto; max-width: 1000px; color: #222; }
+table { border-collapse: collapse; margin: 1em 0; }
+th, td { border: 1px solid #ccc; padding: 4px 10px; }
+td.number { text-align: right; font-variant-numeric: tabular-nums; }
+th { background: #f3f3f3; text-align: left; }
+figure { margin: 1em 0; }
+figure svg, figure img { max-width: 100%; height: auto; }
+nav ul { line-height: 1.6; }";
+
+// One page: the contents, the input and parameters, every summary with its
+// own charts, then the charts, correlations and components of all columns
+fn html_report(
+    context: &ReportContext,
+    output: &PipelineOutput,
+    chart_options: &ChartOptions,
+) -> Result<String, PipelineError> {
+    let mut html = String::new();
+    let title = escape(&context.title);
+    let anchor = |summary: &DataSummary| format!("column-{}", sanitize_file_name(&summary.label()));
+    let (own_charts, shared_charts) = split_charts(output, chart_options);
+
+    html.push_str("<!DOCTYPE html>\n");
+    html.push_str("<html lang=\"en\">\n");
+    html.push_str("<head>\n");
+    html.push_str("<meta charset=\"utf-8\">\n");
+    let _ = writeln!(html, "<title>{}</title>", title);
+    let _ = writeln!(html, "<style>\n{}\n</style>", STYLE);
+    html.push_str("</head>\n");
+    html.push_str("<body>\n");
+    let _ = writeln!(html, "<h1>{}</h1>", title);
+
+    // Table of contents
+    html.push_str("<nav>\n<h2>Contents</h2>\n<ul>\n");
+    html.push_str("<li><a href=\"#input\">Input</a></li>\n");
+    html.push_str("<li><a href=\"#parameters\">Parameters</a></li>\n");
+    html.push_str("<li><a href=\"#summary\">Summary statistics</a><ul>\n");
+    for summary in &output.summaries {
+        let _ = writeln!(
+            html,
+            "<li><a href=\"#{}\">{}</a></li>",
+            anchor(summary),
+            escape(&summary.label())
+        );
+    }
+    html.push_str("</ul></li>\n");
+    if !shared_charts.is_empty() {
+        html.push_str("<li><a href=\"#charts\">Charts</a></li>\n");
+    }
+    if !output.correlations.is_empty() {
+        html.push_str("<li><a href=\"#correlations\">Correlations</a></li>\n");
+    }
+    if output.pca.is_some() {
+        html.push_str("<li><a href=\"#pca\">Principal components</a></li>\n");
+    }
+    html.push_str("</ul>\n</nav>\n");
+
+    html.push_str("<section id=\"input\">\n<h2>Input</h2>\n");
+    html.push_str(&key_value_table(&context.input));
+    html.push_str("</section>\n");
+
+    html.push_str("<section id=\"parameters\">\n<h2>Parameters</h2>\n");
+    html.push_str(&key_value_table(&context.parameters));
+    html.push_str("</section>\n");
+
+    html.push_str("<section id=\"summary\">\n<h2>Summary statistics</h2>\n");
+    html.push_str(&summary_table(&output.summaries));
+    html.push_str("</section>\n");
+
+    for (summary, charts) in output.summaries.iter().zip(&own_charts) {
+        let _ = writeln!(
+            html,
+            "<section id=\"{}\">\n<h2>{}</h2>",
+            anchor(summary),
+            escape(&summary.label())
+        );
+        html.push_str(&summary_table(std::slice::from_ref(summary)));
+        for path in charts {
+            html.push_str(&figure(path)?);
+        }
+        html.push_str("</section>\n");
+    }
+
+    if !shared_charts.is_empty() {
+        html.push_str("<section id=\"charts\">\n<h2>Charts</h2>\n");
+        for path in shared_charts {
+            html.push_str(&figure(path)?);
+        }
+        html.push_str("</section>\n");
+    }
+
+    if !output.correlations.is_empty() {
+        html.push_str("<section id=\"correlations\">\n<h2>Correlations</h2>\n");
+        for matrix in &output.correlations {
+            html.push_str(&correlation_table(matrix));
+        }
+        html.push_str("</section>\n");
+    }
+
+    if let Some(pca) = &output.pca {
+        html.push_str("<section id=\"pca\">\n<h2>Principal components</h2>\n");
+        html.push_str(&pca_table(pca));
+        html.push_str("</section>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+    Ok(html)
+}
+
+// Tables and links to the chart files, ready to paste into a pull request or
+// a wiki page
+fn markdown_report(
+    context: &ReportContext,
+    output: &PipelineOutput,
+    chart_options: &ChartOptions,
+    report_path: &str,
+) -> String {
+    let mut markdown = format!("# {}\n\n", context.title);
+    let link = |chart_path: &str| relative_link(report_path, chart_path);
+
+    for (heading, rows) in [
+        ("Input", &context.input),
+        ("Parameters", &context.parameters),
+    ] {
+        let _ = writeln!(markdown, "## {}\n", heading);
+        for (key, value) in rows {
+            let _ = writeln!(markdown, "- **{}**: {}", key, value.replace('\n', " "));
+        }
+        markdown.push('\n');
+    }
+
+    markdown.push_str("## Summary statistics\n\n| Statistic |");
+    for summary in &output.summaries {
+        let _ = write!(markdown, " {} |", cell(&summary.label()));
+    }
+    markdown.push_str("\n| --- |");
+    markdown.push_str(&" ---: |".repeat(output.summaries.len()));
+    markdown.push('\n');
+    for (label, cells) in statistic_rows(&output.summaries) {
+        let _ = writeln!(markdown, "| {} | {} |", label, cells.join(" | "));
+    }
+    markdown.push('\n');
+
+    if !output.charts.is_empty() {
+        markdown.push_str("## Charts\n\n");
+        let (own_charts, shared_charts) = split_charts(output, chart_options);
+        for (summary, charts) in output.summaries.iter().zip(&own_charts) {
+            if charts.is_empty() {
+                continue;
+            }
+            let links: Vec<String> = charts
+                .iter()
+                .map(|path| format!("[{}]({})", chart_name(path), link(path)))
+                .collect();
+            let _ = writeln!(markdown, "- {}: {}", summary.label(), links.join(", "));
+        }
+        for path in shared_charts {
+            let _ = writeln!(markdown, "- [{}]({})", chart_name(path), link(path));
+        }
+        markdown.push('\n');
+    }
+
+    if !output.correlations.is_empty() {
+        markdown.push_str("## Correlations\n\n");
+        for matrix in &output.correlations {
+            markdown.push_str(&markdown_correlation_table(matrix));
+        }
+    }
+
+    if let Some(pca) = &output.pca {
+        markdown.push_str("## Principal components\n\n");
+        markdown.push_str(&markdown_pca_table(pca));
+    }
+
+    markdown
+}
+
+// The charts of each summary, in summary order, and those covering every
+// column, both in the order they were drawn
+fn split_charts<'a>(
+    output: &'a PipelineOutput,
+    chart_options: &ChartOptions,
+) -> (Vec<Vec<&'a String>>, Vec<&'a String>) {
+    let own_charts: Vec<Vec<&String>> = output
+        .summaries
+        .iter()
+        .map(|summary| {
+            let paths = [
+                chart_options.summary_chart_path(summary),
+                chart_options.histogram_path(summary),
+            ];
+            output
+                .charts
+                .iter()
+                .filter(|path| paths.contains(path))
+                .collect()
+        })
+        .collect();
+    let shared_charts = output
+        .charts
+        .iter()
+        .filter(|path| !own_charts.iter().flatten().any(|own| own == path))
+        .collect();
+
+    (own_charts, shared_charts)
+}
+
+// Count, null count and the floating point statistics, one cell per summary
+fn statistic_rows(summaries: &[DataSummary]) -> Vec<(&'static str, Vec<String>)> {
+    let mut rows = vec![
+        (
+            "Count",
+            summaries.iter().map(|s| s.count.to_string()).collect(),
+        ),
+        (
+            "Null count",
+            summaries.iter().map(|s| s.null_count.to_string()).collect(),
+        ),
+    ];
+    let statistics: Vec<_> = summaries.iter().map(DataSummary::statistics).collect();
+    if let Some(first) = statistics.first() {
+        for (index, (label, _)) in first.iter().enumerate() {
+            rows.push((
+                *label,
+                statistics
+                    .iter()
+                    .map(|row| format!("{:.4}", row[index].1))
+                    .collect(),
+            ));
+        }
+    }
+    rows
+}
+
+// "output/a_histogram.svg" reads as "a histogram"
+fn chart_name(chart_path: &str) -> String {
+    Path::new(chart_path)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(chart_path)
+        .replace('_', " ")
+}
+
+// The path of `target` as seen from the directory holding `from`, so that
+// links keep working wherever the report is written
+fn relative_link(from: &str, target: &str) -> String {
+    let base = match Path::new(from).parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let (Ok(base), Ok(target_path)) = (fs::canonicalize(base), fs::canonicalize(target)) else {
+        return target.to_string();
+    };
+
+    let base: Vec<_> = base.components().collect();
+    let target_path: Vec<_> = target_path.components().collect();
+    let common = base
+        .iter()
+        .zip(&target_path)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut parts: Vec<String> = vec!["..".to_string(); base.len() - common];
+    parts.extend(
+        target_path[common..]
+            .iter()
+            .map(|part| part.as_os_str().to_string_lossy().into_owned()),
+    );
+    parts.join("/")
+}
+
+// Pipes would end a table cell early and line breaks the row, while a
+// backslash would escape the character after it
+fn cell(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace("\r\n", " ")
+        .replace(['\r', '\n'], " ")
+}
+
+fn key_value_table(rows: &[(String, String)]) -> String {
+    let mut html = String::from("<table>\n");
+    for (key, value) in rows {
+        let _ = writeln!(
+            html,
+            "<tr><th>{}</th><td>{}</td></tr>",
+            escape(key),
+            escape(value)
+        );
+    }
+    html.push_str("</table>\n");
+    html
+}
+
+// One column per summary and one row per statistic, as printed by describe
+fn summary_table(summaries: &[DataSummary]) -> String {
+    let mut html = String::from("<table>\n<tr><th>Statistic</th>");
+    for summary in summaries {
+        let _ = write!(html, "<th>{}</th>", escape(&summary.label()));
+    }
+    html.push_str("</tr>\n");
+
+    for (label, cells) in statistic_rows(summaries) {
+        let _ = write!(html, "<tr><th>{}</th>", label);
+        for cell in cells {
+            let _ = write!(html, "<td class=\"number\">{}</td>", cell);
+        }
+        html.push_str("</tr>\n");
+    }
+
+    html.push_str("</table>\n");
+    html
+}
+
+fn correlation_table(matrix: &CorrelationMatrix) -> String {
+    let mut html = format!(
+        "<h3>{}</h3>\n<p>Coefficient, with the p-value and number of rows below it.</p>\n<table>\n<tr><th></th>",
+        matrix.method.name()
+    );
+    for column in &matrix.columns {
+        let _ = write!(html, "<th>{}</th>", escape(column));
+    }
+    html.push_str("</tr>\n");
+
+    for (i, row) in matrix.columns.iter().enumerate() {
+        let _ = write!(html, "<tr><th>{}</th>", escape(row));
+        for j in 0..matrix.columns.len() {
+            let _ = write!(
+                html,
+                "<td class=\"number\">{:.4}<br>p = {:.2e}<br>n = {}</td>",
+                matrix.coefficients[i][j], matrix.p_values[i][j], matrix.observations[i][j]
+            );
+        }
+        html.push_str("</tr>\n");
+    }
+
+    html.push_str("</table>\n");
+    html
+}
+
+fn pca_table(pca: &Pca) -> String {
+    let mut html = String::from(
+        "<table>\n<tr><th>Component</th><th>Eigenvalue</th><th>Explained</th><th>Cumulative</th>",
+    );
+    for column in &pca.columns {
+        let _ = write!(html, "<th>{}</th>", escape(column));
+    }
+    html.push_str("</tr>\n");
+
+    let mut cumulative = 0.0;
+    for (index, ((eigenvalue, ratio), loadings)) in pca
+        .eigenvalues
+        .iter()
+        .zip(&pca.explained_variance_ratio)
+        .zip(&pca.loadings)
+        .enumerate()
+    {
+        cumulative += ratio;
+        let _ = write!(
+            html,
+            "<tr><th>PC{}</th><td class=\"number\">{:.4}</td><td class=\"number\">{:.2}%</td><td class=\"number\">{:.2}%</td>",
+            index + 1,
+            eigenvalue,
+            ratio * 100.0,
+            cumulative * 100.0
+        );
+        for loading in loadings {
+            let _ = write!(html, "<td class=\"number\">{:.4}</td>", loading);
+        }
+        html.push_str("</tr>\n");
+    }
+
+    html.push_str("</table>\n");
+    html
+}
+
+// Headed with the method, one row and column per analyzed column
+fn markdown_correlation_table(matrix: &CorrelationMatrix) -> String {
+    let mut markdown = format!(
+        "### {}\n\nCoefficient, with the p-value and number of rows.\n\n|  |",
+        matrix.method.name()
+    );
+    for column in &matrix.columns {
+        let _ = write!(markdown, " {} |", cell(column));
+    }
+    markdown.push_str("\n| --- |");
+    markdown.push_str(&" ---: |".repeat(matrix.columns.len()));
+    markdown.push('\n');
+
+    for (i, row) in matrix.columns.iter().enumerate() {
+        let _ = write!(markdown, "| **{}** |", cell(row));
+        for j in 0..matrix.columns.len() {
+            let _ = write!(
+                markdown,
+                " {:.4} (p = {:.2e}, n = {}) |",
+                matrix.coefficients[i][j], matrix.p_values[i][j], matrix.observations[i][j]
+            );
+        }
+        markdown.push('\n');
+    }
+
+    markdown.push('\n');
+    markdown
+}
+
+fn markdown_pca_table(pca: &Pca) -> String {
+    let mut markdown = String::from("| Component | Eigenvalue | Explained | Cumulative |");
+    for column in &pca.columns {
+        let _ = write!(markdown, " {} |", cell(column));
+    }
+    markdown.push_str("\n| --- | ---: | ---: | ---: |");
+    markdown.push_str(&" ---: |".repeat(pca.columns.len()));
+    markdown.push('\n');
+
+    let mut cumulative = 0.0;
+    for (index, ((eigenvalue, ratio), loadings)) in pca
+        .eigenvalues
+        .iter()
+        .zip(&pca.explained_variance_ratio)
+        .zip(&pca.loadings)
+        .enumerate()
+    {
+        cumulative += ratio;
+        let _ = write!(
+            markdown,
+            "| PC{} | {:.4} | {:.2}% | {:.2}% |",
+            index + 1,
+            eigenvalue,
+            ratio * 100.0,
+            cumulative * 100.0
+        );
+        for loading in loadings {
+            let _ = write!(markdown, " {:.4} |", loading);
+        }
+        markdown.push('\n');
+    }
+
+    markdown.push('\n');
+    markdown
+}
+
+// Inlines a chart, SVG as markup and bitmaps as a data URI, so that the
+// report needs no other file
+fn figure(chart_path: &str) -> Result<String, PipelineError> {
+    let image = match chart_format(chart_path) {
+        Some(ChartFormat::Svg) | None => {
+            let svg = fs::read_to_string(chart_path)
+                .map_err(|err| PipelineError::io_at(chart_path, err))?;
+            match svg.find("<svg") {
+                Some(start) => svg[start..].trim_end().to_string(),
+                None => svg.trim_end().to_string(),
+            }
+        }
+        Some(format) => {
+            let bytes =
+                fs::read(chart_path).map_err(|err| PipelineError::io_at(chart_path, err))?;
+            let media_type = match format {
+                ChartFormat::Jpeg => "image/jpeg",
+                ChartFormat::Bmp => "image/bmp",
+                _ => "image/png",
+            };
+            format!(
+                "<img src=\"data:{};base64,{}\" alt=\"{}\">",
+                media_type,
+                base64(&bytes),
+                escape(&chart_name(chart_path))
+            )
+        }
+    };
+    Ok(format!(
+        "<figure>\n{}\n<figcaption>{}</figcaption>\n</figure>\n",
+        image,
+        escape(chart_path)
+    ))
+}
+
+// The format a chart was written in, from the extension `ChartOptions::path`
+// gave it
+fn chart_format(chart_path: &str) -> Option<ChartFormat> {
+    let extension = Path::new(chart_path)
+        .extension()
+        .and_then(|extension| extension.to_str())?;
+    [
+        ChartFormat::Svg,
+        ChartFormat::Png,
+        ChartFormat::Jpeg,
+        ChartFormat::Bmp,
+    ]
+    .into_iter()
+    .find(|format| format.extension() == extension)
+}
+
+// Standard base64 with padding
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, byte)| {
+            group | (*byte as u32) << (16 - 8 * i)
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(group >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_processing::summarize_column;
+    use polars::prelude::*;
+
+    fn output(column: &str) -> PipelineOutput {
+        let df = DataFrame::new(vec![Series::new(column, [1.0, 2.0, 4.0])]).unwrap();
+        PipelineOutput {
+            summaries: vec![summarize_column(&df, column).unwrap()],
+            frame: None,
+            correlations: Vec::new(),
+            covariance: None,
+            pca: None,
+            charts: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn base64_pads_to_whole_groups() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foob"), "Zm9vYg==");
+        assert_eq!(base64(b"fooba"), "Zm9vYmE=");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64(&[0xff, 0xfe, 0x00, 0x3e]), "//4APg==");
+    }
+
+    #[test]
+    fn html_escapes_column_names_and_titles() {
+        assert_eq!(
+            escape(r#"<b>"a" & 'b'</b>"#),
+            "&lt;b&gt;&quot;a&quot; &amp; &#39;b&#39;&lt;/b&gt;"
+        );
+
+        let column = r#"<i>"net" & 'gross'"#;
+        let context = ReportContext {
+            title: "Sales <2024> & \"more\"".to_string(),
+            ..ReportContext::default()
+        };
+        let html = html_report(&context, &output(column), &ChartOptions::default()).unwrap();
+
+        assert!(html.contains("<title>Sales &lt;2024&gt; &amp; &quot;more&quot;</title>"));
+        assert!(html.contains("<h1>Sales &lt;2024&gt; &amp; &quot;more&quot;</h1>"));
+        assert!(html.contains("&lt;i&gt;&quot;net&quot; &amp; &#39;gross&#39;"));
+        assert!(!html.contains(column));
+        assert!(!html.contains("<i>"));
+    }
+
+    #[test]
+    fn relative_links_walk_between_directories() {
+        let base = std::env::temp_dir().join(format!("report-links-{}", std::process::id()));
+        for dir in ["reports/sub", "charts"] {
+            fs::create_dir_all(base.join(dir)).unwrap();
+        }
+        for file in [
+            "charts/a.svg",
+            "b.svg",
+            "reports/c.svg",
+            "reports/sub/d.svg",
+        ] {
+            fs::write(base.join(file), "").unwrap();
+        }
+        let path = |file: &str| base.join(file).to_string_lossy().into_owned();
+        let report = path("reports/report.md");
+
+        let links = [
+            relative_link(&report, &path("charts/a.svg")),
+            relative_link(&report, &path("b.svg")),
+            relative_link(&report, &path("reports/c.svg")),
+            relative_link(&report, &path("reports/sub/d.svg")),
+            relative_link(&report, &path("missing.svg")),
+        ];
+        fs::remove_dir_all(&base).unwrap();
+
+        assert_eq!(links[0], "../charts/a.svg");
+        assert_eq!(links[1], "../b.svg");
+        assert_eq!(links[2], "c.svg");
+        assert_eq!(links[3], "sub/d.svg");
+        // Charts that cannot be found keep the path they were given
+        assert_eq!(links[4], path("missing.svg"));
+    }
+
+    #[test]
+    fn markdown_cells_escape_pipes_backslashes_and_line_breaks() {
+        assert_eq!(cell(r"a|b\c"), r"a\|b\\c");
+        assert_eq!(cell("two\nlines\r\nhere"), "two lines here");
+
+        let column = "net|gross\\\nsales";
+        let markdown = markdown_report(
+            &ReportContext::default(),
+            &output(column),
+            &ChartOptions::default(),
+            "report.md",
+        );
+
+        assert!(markdown.contains(r"| Statistic | net\|gross\\ sales |"));
+        // Every row of the table has the same number of cells
+        let unescaped_pipes = |line: &str| {
+            line.replace(r"\\", "")
+                .replace(r"\|", "")
+                .matches('|')
+                .count()
+        };
+        let table: Vec<&str> = markdown
+            .lines()
+            .skip_while(|line| !line.starts_with("| Statistic"))
+            .take_while(|line| line.starts_with('|'))
+            .collect();
+        assert_eq!(table.len(), 15);
+        assert!(
+            table.iter().all(|line| unescaped_pipes(line) == 3),
+            "{:?}",
+            table
+        );
+    }
+}
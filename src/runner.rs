@@ -0,0 +1,545 @@
+// src/runner.rs
+
+use crate::data_processing::{summarize_frame, Analysis, ColumnSelection, DataSummary};
+use crate::derive::{derive_columns, DerivedColumn};
+use crate::error::PipelineError;
+use crate::filter::Filter;
+use crate::input::{load_frame, InputFormat};
+use crate::summary_file::write_summaries;
+use crate::visualization::{
+    draw_charts, parse_chart_size, BinRule, Chart, ChartFormat, ChartOptions,
+};
+use polars::prelude::*;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer};
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// A pipeline definition: named steps run in order, each one working on the
+/// frame left behind by the steps before it.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PipelineConfig {
+    pub steps: Vec<Step>,
+}
+
+#[derive(Debug)]
+pub struct Step {
+    pub name: String,
+    pub kind: StepKind,
+}
+
+// Serde cannot reject unknown keys through `#[serde(flatten)]`, so the name
+// is taken out of the step before the rest is read as a `StepKind`
+impl<'de> Deserialize<'de> for Step {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut table = serde_json::Map::deserialize(deserializer)?;
+        let name = match table.remove("name") {
+            Some(serde_json::Value::String(name)) => name,
+            Some(_) => return Err(D::Error::custom("expected the step name to be a string")),
+            None => return Err(D::Error::missing_field("name")),
+        };
+        let kind = StepKind::deserialize(serde_json::Value::Object(table))
+            .map_err(|err| D::Error::custom(format!("step '{}': {}", name, err)))?;
+        Ok(Step { name, kind })
+    }
+}
+
+/// What a step does, picked by its `type` key.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase", deny_unknown_fields)]
+pub enum StepKind {
+    /// Read a data file, replacing the current frame
+    Load {
+        path: String,
+        format: Option<InputFormat>,
+        #[serde(default)]
+        columns: Vec<String>,
+    },
+    /// Keep the rows matching `filter`, an expression as for `--filter`
+    Filter { filter: String },
+    /// Add or replace the column defined by `column`, written as
+    /// `name = expression` as for `--derive`
+    Derive { column: String },
+    /// Compute the summary statistics, optionally per group and saved to a file
+    Summarize {
+        #[serde(default)]
+        columns: Vec<String>,
+        group_by: Option<String>,
+        output: Option<String>,
+    },
+    /// Draw charts from the last summary into the output directory
+    Plot {
+        #[serde(default = "Chart::all")]
+        charts: Vec<Chart>,
+        bins: Option<String>,
+        /// Chart file format, SVG by default
+        format: Option<ChartFormat>,
+        /// Chart size as `WIDTHxHEIGHT` in points
+        size: Option<String>,
+        /// Resolution of bitmap charts
+        dpi: Option<u32>,
+        /// Directory the charts are saved in, `output` by default
+        output_dir: Option<String>,
+        /// Chart file names, as for `--name-template`
+        name_template: Option<String>,
+        /// Built-in theme name or theme file, as for `--theme`
+        theme: Option<String>,
+    },
+}
+
+impl StepKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            StepKind::Load { .. } => "load",
+            StepKind::Filter { .. } => "filter",
+            StepKind::Derive { .. } => "derive",
+            StepKind::Summarize { .. } => "summarize",
+            StepKind::Plot { .. } => "plot",
+        }
+    }
+}
+
+/// Outcome of one step.
+#[derive(Debug)]
+pub enum StepStatus {
+    Succeeded(StepOutput),
+    Failed(PipelineError),
+    /// Not run because an earlier step failed
+    Skipped,
+}
+
+/// What a successful step produced, for the caller to show.
+#[derive(Debug)]
+pub enum StepOutput {
+    Loaded {
+        rows: usize,
+        columns: usize,
+    },
+    Filtered {
+        kept: usize,
+        rows: usize,
+    },
+    Derived {
+        column: String,
+    },
+    Summarized {
+        summaries: Vec<DataSummary>,
+        /// File the statistics were written to
+        output: Option<String>,
+    },
+    /// Paths of the chart files written
+    Plotted(Vec<String>),
+}
+
+#[derive(Debug)]
+pub struct StepReport {
+    pub name: String,
+    pub kind: &'static str,
+    pub status: StepStatus,
+    pub duration: Duration,
+}
+
+/// Reads a pipeline definition, as TOML or YAML depending on the extension.
+pub fn load_config(file_path: &str) -> Result<PipelineConfig, PipelineError> {
+    let text = fs::read_to_string(file_path).map_err(|err| PipelineError::io_at(file_path, err))?;
+    let extension = Path::new(file_path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_ascii_lowercase());
+    let invalid = |err: String| PipelineError::Parse(format!("{}: {}", file_path, err));
+
+    match extension.as_deref() {
+        Some("toml") => toml::from_str(&text).map_err(|err| invalid(err.to_string())),
+        Some("yaml") | Some("yml") => {
+            serde_yaml::from_str(&text).map_err(|err| invalid(err.to_string()))
+        }
+        _ => Err(invalid(
+            "expected a .toml, .yaml or .yml pipeline file".to_string(),
+        )),
+    }
+}
+
+// What the steps hand on to each other
+#[derive(Default)]
+struct State {
+    frame: Option<DataFrame>,
+    analysis: Option<Analysis>,
+    /// Stem of the last loaded file, for chart names
+    dataset: Option<String>,
+}
+
+/// Runs the steps in order and reports the status and duration of each,
+/// also passing every report to `on_step` as soon as the step is over. Once
+/// a step fails the remaining ones are skipped.
+pub fn run_pipeline(
+    config: &PipelineConfig,
+    mut on_step: impl FnMut(&StepReport),
+) -> Vec<StepReport> {
+    let mut state = State::default();
+    let mut failed = false;
+
+    config
+        .steps
+        .iter()
+        .map(|step| {
+            let kind = step.kind.name();
+            let report = if failed {
+                StepReport {
+                    name: step.name.clone(),
+                    kind,
+                    status: StepStatus::Skipped,
+                    duration: Duration::ZERO,
+                }
+            } else {
+                let start = Instant::now();
+                let status = match run_step(&step.kind, &mut state) {
+                    Ok(output) => StepStatus::Succeeded(output),
+                    Err(err) => {
+                        failed = true;
+                        StepStatus::Failed(err)
+                    }
+                };
+                StepReport {
+                    name: step.name.clone(),
+                    kind,
+                    status,
+                    duration: start.elapsed(),
+                }
+            };
+
+            on_step(&report);
+            report
+        })
+        .collect()
+}
+
+fn run_step(kind: &StepKind, state: &mut State) -> Result<StepOutput, PipelineError> {
+    let output = match kind {
+        StepKind::Load {
+            path,
+            format,
+            columns,
+        } => {
+            let format = format.unwrap_or_else(|| InputFormat::from_path(path));
+            let columns = if columns.is_empty() {
+                None
+            } else {
+                Some(columns.clone())
+            };
+            let df = load_frame(path, format, columns)?;
+            let output = StepOutput::Loaded {
+                rows: df.height(),
+                columns: df.width(),
+            };
+
+            state.frame = Some(df);
+            state.analysis = None;
+            state.dataset = Path::new(path)
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .map(str::to_string);
+            output
+        }
+        StepKind::Filter { filter } => {
+            let filter = Filter::parse(filter)?;
+            let df = current_frame(state)?;
+            let filtered = filter.apply(df.clone())?;
+            let output = StepOutput::Filtered {
+                kept: filtered.height(),
+                rows: df.height(),
+            };
+
+            state.frame = Some(filtered);
+            state.analysis = None;
+            output
+        }
+        StepKind::Derive { column } => {
+            let definition = DerivedColumn::parse(column)?;
+            let df = current_frame(state)?;
+            let derived = derive_columns(df.clone(), std::slice::from_ref(&definition))?;
+
+            state.frame = Some(derived);
+            state.analysis = None;
+            StepOutput::Derived {
+                column: definition.name,
+            }
+        }
+        StepKind::Summarize {
+            columns,
+            group_by,
+            output,
+        } => {
+            let selection = if columns.is_empty() {
+                ColumnSelection::AllNumeric
+            } else {
+                ColumnSelection::Named(columns.clone())
+            };
+            let analysis = summarize_frame(current_frame(state)?, &selection, group_by.as_deref())?;
+            if let Some(output_path) = output {
+                write_summaries(&analysis.summaries, output_path)?;
+            }
+
+            let summaries = analysis.summaries.clone();
+            state.analysis = Some(analysis);
+            StepOutput::Summarized {
+                summaries,
+                output: output.clone(),
+            }
+        }
+        StepKind::Plot {
+            charts,
+            bins,
+            format,
+            size,
+            dpi,
+            output_dir,
+            name_template,
+            theme,
+        } => {
+            let analysis = state.analysis.as_ref().ok_or_else(|| {
+                PipelineError::Config("a summarize step must run before plot".to_string())
+            })?;
+            let bins = match bins {
+                Some(bins) => bins.parse::<BinRule>().map_err(PipelineError::Config)?,
+                None => BinRule::Sturges,
+            };
+            let size = size
+                .as_deref()
+                .map(parse_chart_size)
+                .transpose()
+                .map_err(PipelineError::Config)?;
+            let defaults = ChartOptions::default();
+            let mut options = ChartOptions::new(
+                theme.as_deref().unwrap_or("light"),
+                format.unwrap_or(defaults.format),
+                size,
+                dpi.unwrap_or(defaults.dpi),
+                output_dir.as_deref().unwrap_or(&defaults.output_dir),
+                name_template.as_deref().unwrap_or(&defaults.name_template),
+            )?;
+            if let Some(dataset) = &state.dataset {
+                options.dataset = dataset.clone();
+            }
+
+            // Ensure the output directory exists
+            fs::create_dir_all(&options.output_dir)
+                .map_err(|err| PipelineError::io_at(&options.output_dir, err))?;
+
+            StepOutput::Plotted(draw_charts(
+                charts,
+                Some(&analysis.frame),
+                &analysis.summaries,
+                bins,
+                &options,
+            )?)
+        }
+    };
+
+    Ok(output)
+}
+
+fn current_frame(state: &State) -> Result<&DataFrame, PipelineError> {
+    state
+        .frame
+        .as_ref()
+        .ok_or_else(|| PipelineError::Config("a load step must run first".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example_pipeline_runs_into_missing_directories() {
+        let output_dir = std::env::temp_dir().join(format!("example-{}", std::process::id()));
+        let output_dir = output_dir.to_str().unwrap().to_string();
+        let mut config = load_config("pipelines/example.toml").unwrap();
+        for step in &mut config.steps {
+            match &mut step.kind {
+                StepKind::Summarize { output, .. } => {
+                    *output = Some(format!("{}/statistics/summary.json", output_dir))
+                }
+                StepKind::Plot {
+                    output_dir: charts, ..
+                } => *charts = Some(format!("{}/charts", output_dir)),
+                _ => {}
+            }
+        }
+
+        let reports = run_pipeline(&config, |_| {});
+        let summary_written = Path::new(&output_dir)
+            .join("statistics/summary.json")
+            .exists();
+        fs::remove_dir_all(&output_dir).unwrap();
+
+        assert_eq!(reports.len(), 5);
+        for report in &reports {
+            assert!(
+                matches!(report.status, StepStatus::Succeeded(_)),
+                "step '{}' ended with {:?}",
+                report.name,
+                report.status
+            );
+        }
+        assert!(summary_written);
+        match &reports[4].status {
+            StepStatus::Succeeded(StepOutput::Plotted(paths)) => {
+                assert!(paths.iter().all(|path| path.starts_with(&output_dir)))
+            }
+            other => panic!("expected chart paths, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn misspelled_step_keys_are_rejected() {
+        let config = r#"
+            [[steps]]
+            name = "charts"
+            type = "plot"
+            output_directory = "out"
+        "#;
+        let err = toml::from_str::<PipelineConfig>(config).unwrap_err();
+        assert!(
+            err.to_string().contains("unknown field `output_directory`"),
+            "{}",
+            err
+        );
+
+        let config = r#"
+            steps:
+              - name: charts
+                type: plot
+                output_directory: out
+        "#;
+        let err = serde_yaml::from_str::<PipelineConfig>(config).unwrap_err();
+        assert!(err.to_string().contains("unknown field `output_directory`"));
+    }
+
+    #[test]
+    fn steps_are_read_with_their_defaults() {
+        let config = r#"
+            steps:
+              - name: data
+                type: load
+                path: data/large_dataset.csv
+                format: csv
+              - name: stats
+                type: summarize
+                group_by: category
+              - name: charts
+                type: plot
+                dpi: 192
+        "#;
+        let config: PipelineConfig = serde_yaml::from_str(config).unwrap();
+
+        let names: Vec<_> = config.steps.iter().map(|step| step.name.as_str()).collect();
+        assert_eq!(names, ["data", "stats", "charts"]);
+        match &config.steps[0].kind {
+            StepKind::Load {
+                path,
+                format,
+                columns,
+            } => {
+                assert_eq!(path, "data/large_dataset.csv");
+                assert_eq!(*format, Some(InputFormat::Csv));
+                assert!(columns.is_empty());
+            }
+            other => panic!("expected a load step, got {:?}", other),
+        }
+        match &config.steps[1].kind {
+            StepKind::Summarize {
+                columns,
+                group_by,
+                output,
+            } => {
+                assert!(columns.is_empty());
+                assert_eq!(group_by.as_deref(), Some("category"));
+                assert_eq!(*output, None);
+            }
+            other => panic!("expected a summarize step, got {:?}", other),
+        }
+        match &config.steps[2].kind {
+            StepKind::Plot {
+                charts, dpi, bins, ..
+            } => {
+                assert_eq!(*charts, Chart::all());
+                assert_eq!(*dpi, Some(192));
+                assert_eq!(*bins, None);
+            }
+            other => panic!("expected a plot step, got {:?}", other),
+        }
+
+        let config = r#"
+            [[steps]]
+            name = "sorted"
+            type = "sort"
+        "#;
+        let err = toml::from_str::<PipelineConfig>(config).unwrap_err();
+        assert!(
+            err.to_string().contains("unknown variant `sort`"),
+            "{}",
+            err
+        );
+
+        let config = r#"
+            [[steps]]
+            type = "load"
+            path = "a.csv"
+        "#;
+        let err = toml::from_str::<PipelineConfig>(config).unwrap_err();
+        assert!(err.to_string().contains("missing field `name`"), "{}", err);
+    }
+
+    #[test]
+    fn steps_after_a_failure_are_skipped() {
+        let config = r#"
+            [[steps]]
+            name = "data"
+            type = "load"
+            path = "data/missing.csv"
+
+            [[steps]]
+            name = "stats"
+            type = "summarize"
+        "#;
+        let config: PipelineConfig = toml::from_str(config).unwrap();
+
+        let mut seen = Vec::new();
+        let reports = run_pipeline(&config, |report| seen.push(report.name.clone()));
+
+        assert_eq!(seen, ["data", "stats"]);
+        assert!(matches!(
+            reports[0].status,
+            StepStatus::Failed(PipelineError::Io(_))
+        ));
+        assert!(matches!(reports[1].status, StepStatus::Skipped));
+        assert_eq!(reports[1].duration, Duration::ZERO);
+    }
+
+    #[test]
+    fn plot_needs_an_earlier_summarize_step() {
+        let config = r#"
+            [[steps]]
+            name = "data"
+            type = "load"
+            path = "data/large_dataset.csv"
+
+            [[steps]]
+            name = "charts"
+            type = "plot"
+        "#;
+        let config: PipelineConfig = toml::from_str(config).unwrap();
+
+        let reports = run_pipeline(&config, |_| {});
+
+        assert!(matches!(reports[0].status, StepStatus::Succeeded(_)));
+        match &reports[1].status {
+            StepStatus::Failed(PipelineError::Config(message)) => {
+                assert_eq!(message, "a summarize step must run before plot")
+            }
+            other => panic!("expected a configuration error, got {:?}", other),
+        }
+    }
+}
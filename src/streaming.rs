@@ -0,0 +1,355 @@
+// src/streaming.rs
+
+use crate::data_processing::{quantile, ColumnSelection, DataSummary, Moments};
+use crate::derive::{derive_columns, derived_schema, input_columns, DerivedColumn};
+use crate::error::PipelineError;
+use crate::filter::Filter;
+use crate::input::{for_each_batch, read_schema, InputFormat};
+use polars::prelude::*;
+
+/// Summarizes the selected columns, including the `derived` ones, over the
+/// rows matching `filter` chunk by chunk, never holding more than one chunk
+/// of rows in memory.
+///
+/// Count, nulls, min, max and the moments are exact; the median and quartiles
+/// are P² estimates since exact order statistics would need every value.
+pub fn process_data_streaming(
+    file_path: &str,
+    format: InputFormat,
+    selection: &ColumnSelection,
+    derived: &[DerivedColumn],
+    filter: Option<&Filter>,
+    chunk_size: usize,
+) -> Result<Vec<DataSummary>, PipelineError> {
+    // Resolve and type-check the columns from the schema alone
+    let file_schema = read_schema(file_path, format)?;
+    let schema = derived_schema(&file_schema, derived)?;
+    let column_names: Vec<String> = match selection {
+        ColumnSelection::Named(columns) => columns.clone(),
+        ColumnSelection::AllNumeric => schema
+            .iter()
+            .filter(|(_, dtype)| dtype.is_numeric())
+            .map(|(name, _)| name.to_string())
+            .collect(),
+    };
+
+    if column_names.is_empty() {
+        return Err(PipelineError::EmptyData(format!(
+            "'{}' has no numeric columns",
+            file_path
+        )));
+    }
+
+    for column_name in &column_names {
+        let dtype = schema
+            .get(column_name)
+            .ok_or_else(|| PipelineError::MissingColumn(column_name.clone()))?;
+        if !dtype.is_numeric() {
+            return Err(PipelineError::TypeMismatch {
+                column: column_name.clone(),
+                dtype: dtype.to_string(),
+            });
+        }
+    }
+
+    // Compile the filter once and read the columns it and the derived ones
+    // need along with the analyzed ones
+    let predicate = filter.map(|filter| filter.to_expr(&schema)).transpose()?;
+    let mut columns = column_names.clone();
+    columns.extend(filter.map(Filter::columns).unwrap_or_default());
+    let projection = input_columns(&columns, derived);
+
+    let mut accumulators: Vec<StreamingSummary> = column_names
+        .iter()
+        .map(|_| StreamingSummary::new())
+        .collect();
+
+    for_each_batch(
+        file_path,
+        format,
+        &file_schema,
+        projection,
+        chunk_size,
+        |chunk| {
+            let chunk = derive_columns(chunk, derived)?;
+            let chunk = match &predicate {
+                Some(predicate) => chunk.lazy().filter(predicate.clone()).collect()?,
+                None => chunk,
+            };
+            for (column_name, accumulator) in column_names.iter().zip(&mut accumulators) {
+                let series = chunk.column(column_name)?.cast(&DataType::Float64)?;
+                accumulator.push_chunk(series.f64()?);
+            }
+            Ok(())
+        },
+    )?;
+
+    column_names
+        .iter()
+        .zip(accumulators)
+        .map(|(column_name, accumulator)| accumulator.finish(column_name))
+        .collect()
+}
+
+/// Constant-size running state for one column.
+struct StreamingSummary {
+    null_count: usize,
+    min: f64,
+    max: f64,
+    moments: Moments,
+    q1: P2Quantile,
+    median: P2Quantile,
+    q3: P2Quantile,
+}
+
+impl StreamingSummary {
+    fn new() -> Self {
+        StreamingSummary {
+            null_count: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            moments: Moments::default(),
+            q1: P2Quantile::new(0.25),
+            median: P2Quantile::new(0.5),
+            q3: P2Quantile::new(0.75),
+        }
+    }
+
+    fn push_chunk(&mut self, values: &Float64Chunked) {
+        self.null_count += values.null_count();
+        for value in values.into_iter().flatten() {
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+            self.moments.push(value);
+            self.q1.push(value);
+            self.median.push(value);
+            self.q3.push(value);
+        }
+    }
+
+    fn finish(self, column_name: &str) -> Result<DataSummary, PipelineError> {
+        let count = self.median.count;
+        if count == 0 {
+            return Err(PipelineError::EmptyData(format!(
+                "column '{}' has no non-null values",
+                column_name
+            )));
+        }
+
+        let q1 = self.q1.estimate();
+        let q3 = self.q3.estimate();
+        let variance = self.moments.variance();
+
+        Ok(DataSummary {
+            column: column_name.to_string(),
+            group: None,
+            count,
+            null_count: self.null_count,
+            min: self.min,
+            max: self.max,
+            mean: self.moments.mean(),
+            median: self.median.estimate(),
+            std_dev: variance.sqrt(),
+            variance,
+            q1,
+            q3,
+            iqr: q3 - q1,
+            skewness: self.moments.skewness(),
+            kurtosis: self.moments.kurtosis(),
+        })
+    }
+}
+
+/// P² estimator of a single quantile (Jain & Chlamtac, 1985).
+///
+/// Tracks five markers whose heights are adjusted with piecewise-parabolic
+/// interpolation as values arrive, so memory use is constant.
+struct P2Quantile {
+    p: f64,
+    count: usize,
+    heights: [f64; 5],
+    positions: [f64; 5],
+    desired: [f64; 5],
+    increments: [f64; 5],
+}
+
+impl P2Quantile {
+    fn new(p: f64) -> Self {
+        P2Quantile {
+            p,
+            count: 0,
+            heights: [0.0; 5],
+            positions: [1.0, 2.0, 3.0, 4.0, 5.0],
+            desired: [1.0, 1.0 + 2.0 * p, 1.0 + 4.0 * p, 3.0 + 2.0 * p, 5.0],
+            increments: [0.0, p / 2.0, p, (1.0 + p) / 2.0, 1.0],
+        }
+    }
+
+    fn push(&mut self, value: f64) {
+        // The first five values seed the markers
+        if self.count < 5 {
+            self.heights[self.count] = value;
+            self.count += 1;
+            if self.count == 5 {
+                self.heights.sort_by(|a, b| a.total_cmp(b));
+            }
+            return;
+        }
+        self.count += 1;
+
+        // Find the cell the value falls into, widening the extremes if needed
+        let cell = if value < self.heights[0] {
+            self.heights[0] = value;
+            0
+        } else if value >= self.heights[4] {
+            self.heights[4] = value;
+            3
+        } else {
+            (1..5).find(|&i| value < self.heights[i]).unwrap() - 1
+        };
+
+        for position in &mut self.positions[cell + 1..] {
+            *position += 1.0;
+        }
+        for (desired, increment) in self.desired.iter_mut().zip(self.increments) {
+            *desired += increment;
+        }
+
+        // Move the inner markers towards their desired positions
+        for i in 1..4 {
+            let offset = self.desired[i] - self.positions[i];
+            if (offset >= 1.0 && self.positions[i + 1] - self.positions[i] > 1.0)
+                || (offset <= -1.0 && self.positions[i - 1] - self.positions[i] < -1.0)
+            {
+                let step = offset.signum();
+                let candidate = self.parabolic(i, step);
+                self.heights[i] =
+                    if self.heights[i - 1] < candidate && candidate < self.heights[i + 1] {
+                        candidate
+                    } else {
+                        self.linear(i, step)
+                    };
+                self.positions[i] += step;
+            }
+        }
+    }
+
+    fn estimate(&self) -> f64 {
+        if self.count > 5 {
+            return self.heights[2];
+        }
+
+        // The markers have not moved yet, so the exact quantile is still known
+        let mut seen = self.heights[..self.count].to_vec();
+        seen.sort_by(|a, b| a.total_cmp(b));
+        quantile(&seen, self.p)
+    }
+
+    fn parabolic(&self, i: usize, step: f64) -> f64 {
+        let (q, n) = (&self.heights, &self.positions);
+        q[i] + step / (n[i + 1] - n[i - 1])
+            * ((n[i] - n[i - 1] + step) * (q[i + 1] - q[i]) / (n[i + 1] - n[i])
+                + (n[i + 1] - n[i] - step) * (q[i] - q[i - 1]) / (n[i] - n[i - 1]))
+    }
+
+    fn linear(&self, i: usize, step: f64) -> f64 {
+        let neighbour = if step > 0.0 { i + 1 } else { i - 1 };
+        self.heights[i]
+            + step * (self.heights[neighbour] - self.heights[i])
+                / (self.positions[neighbour] - self.positions[i])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROBABILITIES: [f64; 3] = [0.25, 0.5, 0.75];
+
+    fn estimate(values: &[f64], p: f64) -> f64 {
+        let mut estimator = P2Quantile::new(p);
+        for &value in values {
+            estimator.push(value);
+        }
+        estimator.estimate()
+    }
+
+    fn exact(values: &[f64], p: f64) -> f64 {
+        let mut sorted: Vec<f64> = values.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        quantile(&sorted, p)
+    }
+
+    #[test]
+    fn up_to_five_values_are_exact() {
+        let values = [7.0, -2.0, 3.5, 10.0, 0.0];
+        for count in 1..=5 {
+            for p in PROBABILITIES {
+                assert_eq!(
+                    estimate(&values[..count], p),
+                    exact(&values[..count], p),
+                    "p = {} over {} values",
+                    p,
+                    count
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn six_values_stay_between_the_neighbouring_order_statistics() {
+        let values = [7.0, -2.0, 3.5, 10.0, 0.0, 4.0];
+        let mut sorted: Vec<f64> = values.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        for p in PROBABILITIES {
+            let position = p * (sorted.len() - 1) as f64;
+            let lower = sorted[(position.floor() as usize).saturating_sub(1)];
+            let upper = sorted[(position.ceil() as usize + 1).min(sorted.len() - 1)];
+            let estimate = estimate(&values, p);
+            assert!(
+                (lower..=upper).contains(&estimate),
+                "p = {}: {} not in [{}, {}]",
+                p,
+                estimate,
+                lower,
+                upper
+            );
+        }
+    }
+
+    #[test]
+    fn many_values_approach_the_exact_quantiles() {
+        // A deterministic shuffle of 0..10000
+        let values: Vec<f64> = (0..10_000u64)
+            .map(|i| ((i * 7_919) % 10_000) as f64)
+            .collect();
+        for p in PROBABILITIES {
+            let (estimate, exact) = (estimate(&values, p), exact(&values, p));
+            assert!(
+                (estimate - exact).abs() < 100.0,
+                "p = {}: estimate {} vs exact {}",
+                p,
+                estimate,
+                exact
+            );
+        }
+    }
+
+    #[test]
+    fn skewed_values_approach_the_exact_quantiles() {
+        // Exponentially distributed values in a deterministic shuffle
+        let values: Vec<f64> = (1..=5_000u64)
+            .map(|i| -((((i * 761) % 5_000) as f64 + 0.5) / 5_000.0).ln())
+            .collect();
+        for p in PROBABILITIES {
+            let (estimate, exact) = (estimate(&values, p), exact(&values, p));
+            assert!(
+                (estimate - exact).abs() / exact < 0.05,
+                "p = {}: estimate {} vs exact {}",
+                p,
+                estimate,
+                exact
+            );
+        }
+    }
+}
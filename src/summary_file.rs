@@ -0,0 +1,199 @@
+// src/summary_file.rs
+
+use crate::data_processing::{DataSummary, GroupKey};
+use crate::error::PipelineError;
+use crate::input::{create_file, load_frame, InputFormat};
+use polars::prelude::*;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+/// File formats summaries can be saved in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SummaryFormat {
+    Json,
+    Csv,
+    Parquet,
+}
+
+impl SummaryFormat {
+    /// Picks the format from the file extension.
+    pub fn from_path(file_path: &str) -> Option<SummaryFormat> {
+        let extension = Path::new(file_path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("json") => Some(SummaryFormat::Json),
+            Some("csv") => Some(SummaryFormat::Csv),
+            Some("parquet") | Some("pq") => Some(SummaryFormat::Parquet),
+            _ => None,
+        }
+    }
+}
+
+/// Checks a `--summary-out` path for clap, so that an unsupported extension is
+/// reported before any data is read.
+pub fn parse_summary_path(file_path: &str) -> Result<String, String> {
+    match SummaryFormat::from_path(file_path) {
+        Some(_) => Ok(file_path.to_string()),
+        None => Err(format!(
+            "expected a .json, .csv or .parquet file, got '{}'",
+            file_path
+        )),
+    }
+}
+
+/// Writes the summaries to `file_path` in the format given by its extension.
+///
+/// JSON holds an array of summary objects; CSV and Parquet hold one row per
+/// summary, with the group flattened into `group_column` and `group`.
+pub fn write_summaries(summaries: &[DataSummary], file_path: &str) -> Result<(), PipelineError> {
+    let format = SummaryFormat::from_path(file_path)
+        .ok_or_else(|| PipelineError::Parse(format!("unsupported summary file '{}'", file_path)))?;
+    let file = create_file(file_path)?;
+
+    match format {
+        SummaryFormat::Json => serde_json::to_writer_pretty(file, summaries)
+            .map_err(|err| PipelineError::Io(err.into()))?,
+        SummaryFormat::Csv => {
+            CsvWriter::new(file)
+                .has_header(true)
+                .finish(&mut summary_frame(summaries)?)?;
+        }
+        SummaryFormat::Parquet => {
+            ParquetWriter::new(file).finish(&mut summary_frame(summaries)?)?;
+        }
+    }
+
+    Ok(())
+}
+
+// One row per summary, one column per field
+fn summary_frame(summaries: &[DataSummary]) -> Result<DataFrame, PipelineError> {
+    let text = |name: &str, field: fn(&DataSummary) -> Option<&str>| {
+        Series::new(name, summaries.iter().map(field).collect::<Vec<_>>())
+    };
+    let count = |name: &str, field: fn(&DataSummary) -> usize| {
+        Series::new(
+            name,
+            summaries
+                .iter()
+                .map(|summary| field(summary) as u64)
+                .collect::<Vec<_>>(),
+        )
+    };
+    let float = |name: &str, field: fn(&DataSummary) -> f64| {
+        Series::new(name, summaries.iter().map(field).collect::<Vec<_>>())
+    };
+
+    Ok(DataFrame::new(vec![
+        text("column", |s| Some(s.column.as_str())),
+        text("group_column", |s| {
+            s.group.as_ref().map(|g| g.column.as_str())
+        }),
+        text("group", |s| s.group.as_ref().map(|g| g.value.as_str())),
+        count("count", |s| s.count),
+        count("null_count", |s| s.null_count),
+        float("min", |s| s.min),
+        float("q1", |s| s.q1),
+        float("median", |s| s.median),
+        float("q3", |s| s.q3),
+        float("max", |s| s.max),
+        float("iqr", |s| s.iqr),
+        float("mean", |s| s.mean),
+        float("std_dev", |s| s.std_dev),
+        float("variance", |s| s.variance),
+        float("skewness", |s| s.skewness),
+        float("kurtosis", |s| s.kurtosis),
+    ])?)
+}
+
+/// Reads summaries saved by `write_summaries`, in the format given by the
+/// file extension.
+pub fn read_summaries(file_path: &str) -> Result<Vec<DataSummary>, PipelineError> {
+    let format = SummaryFormat::from_path(file_path)
+        .ok_or_else(|| PipelineError::Parse(format!("unsupported summary file '{}'", file_path)))?;
+
+    match format {
+        SummaryFormat::Json => {
+            let file = File::open(file_path).map_err(|err| PipelineError::io_at(file_path, err))?;
+            serde_json::from_reader(BufReader::new(file))
+                .map_err(|err| PipelineError::Parse(format!("{}: {}", file_path, err)))
+        }
+        SummaryFormat::Csv => summaries_from_frame(&load_frame(file_path, InputFormat::Csv, None)?),
+        SummaryFormat::Parquet => {
+            summaries_from_frame(&load_frame(file_path, InputFormat::Parquet, None)?)
+        }
+    }
+}
+
+// The inverse of `summary_frame`
+fn summaries_from_frame(df: &DataFrame) -> Result<Vec<DataSummary>, PipelineError> {
+    let text = |name: &str| -> Result<Vec<Option<String>>, PipelineError> {
+        let series = df.column(name)?.cast(&DataType::Utf8)?;
+        let values = series.utf8()?.into_iter();
+        Ok(values.map(|value| value.map(String::from)).collect())
+    };
+    let count = |name: &str| -> Result<Vec<usize>, PipelineError> {
+        let series = df.column(name)?.cast(&DataType::UInt64)?;
+        let values = series.u64()?.into_iter();
+        Ok(values.map(|value| value.unwrap_or(0) as usize).collect())
+    };
+    let float = |name: &str| -> Result<Vec<f64>, PipelineError> {
+        let series = df.column(name)?.cast(&DataType::Float64)?;
+        let values = series.f64()?.into_iter();
+        Ok(values.map(|value| value.unwrap_or(f64::NAN)).collect())
+    };
+
+    let columns = text("column")?;
+    let group_columns = text("group_column")?;
+    let groups = text("group")?;
+    let counts = count("count")?;
+    let null_counts = count("null_count")?;
+    let min = float("min")?;
+    let q1 = float("q1")?;
+    let median = float("median")?;
+    let q3 = float("q3")?;
+    let max = float("max")?;
+    let iqr = float("iqr")?;
+    let mean = float("mean")?;
+    let std_dev = float("std_dev")?;
+    let variance = float("variance")?;
+    let skewness = float("skewness")?;
+    let kurtosis = float("kurtosis")?;
+
+    (0..df.height())
+        .map(|row| {
+            let column = columns[row].clone().ok_or_else(|| {
+                PipelineError::Parse(format!("summary row {} has no column name", row + 1))
+            })?;
+            let group = match (&group_columns[row], &groups[row]) {
+                (Some(column), Some(value)) => Some(GroupKey {
+                    column: column.clone(),
+                    value: value.clone(),
+                }),
+                _ => None,
+            };
+
+            Ok(DataSummary {
+                column,
+                group,
+                count: counts[row],
+                null_count: null_counts[row],
+                min: min[row],
+                max: max[row],
+                mean: mean[row],
+                median: median[row],
+                std_dev: std_dev[row],
+                variance: variance[row],
+                q1: q1[row],
+                q3: q3[row],
+                iqr: iqr[row],
+                skewness: skewness[row],
+                kurtosis: kurtosis[row],
+            })
+        })
+        .collect()
+}
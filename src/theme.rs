@@ -0,0 +1,381 @@
+// src/theme.rs
+
+use crate::error::PipelineError;
+use crate::visualization::parse_chart_size;
+use plotters::style::{Palette, Palette99, RGBColor};
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+/// Names of the built-in themes.
+pub const BUILT_IN_THEMES: [&str; 4] = ["light", "dark", "print", "colorblind"];
+
+/// Colors, fonts and spacing shared by every chart.
+///
+/// Sizes are in points and scaled with the chart resolution like everything
+/// else.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    /// Built-in name or the path of the theme file
+    pub name: String,
+    pub background: RGBColor,
+    /// Captions, tick labels, axis descriptions and legends
+    pub text: RGBColor,
+    /// Axis lines and the legend border
+    pub axis: RGBColor,
+    /// Major grid lines; minor ones are drawn half as strong
+    pub grid: RGBColor,
+    /// Markers drawn over the bars, such as error bars and medians
+    pub marker: RGBColor,
+    /// Single-series bars: histograms, spreads and explained variance
+    pub primary: RGBColor,
+    /// Second series: means, cumulative explained variance
+    pub secondary: RGBColor,
+    /// One color per group or column, repeated when there are more
+    pub palette: Vec<RGBColor>,
+    /// Heatmap colors for -1 and +1, blended with white towards 0
+    pub negative: RGBColor,
+    pub positive: RGBColor,
+    /// Font family, e.g. "sans-serif" or "DejaVu Serif"
+    pub font: String,
+    pub caption_size: u32,
+    pub label_size: u32,
+    /// Draw the caption above every chart
+    pub captions: bool,
+    pub margin: u32,
+    /// Room for the tick labels left of and below the plot
+    pub label_area: u32,
+    /// Chart size used when none is given on the command line
+    pub size: Option<(u32, u32)>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::light()
+    }
+}
+
+impl Theme {
+    /// Black on white with saturated colors, as the charts have always been
+    /// drawn.
+    pub fn light() -> Self {
+        Theme {
+            name: "light".to_string(),
+            background: RGBColor(255, 255, 255),
+            text: RGBColor(0, 0, 0),
+            axis: RGBColor(0, 0, 0),
+            grid: RGBColor(204, 204, 204),
+            marker: RGBColor(0, 0, 0),
+            primary: RGBColor(0, 0, 255),
+            secondary: RGBColor(255, 0, 0),
+            palette: (0..Palette99::COLORS.len())
+                .map(|index| {
+                    let (r, g, b) = Palette99::COLORS[index];
+                    RGBColor(r, g, b)
+                })
+                .collect(),
+            negative: RGBColor(33, 102, 172),
+            positive: RGBColor(178, 24, 43),
+            font: "sans-serif".to_string(),
+            caption_size: 40,
+            label_size: 12,
+            captions: true,
+            margin: 10,
+            label_area: 50,
+            size: None,
+        }
+    }
+
+    /// Light text on a dark grey background, for slides and dark pages.
+    pub fn dark() -> Self {
+        Theme {
+            name: "dark".to_string(),
+            background: RGBColor(30, 30, 30),
+            text: RGBColor(230, 230, 230),
+            axis: RGBColor(170, 170, 170),
+            grid: RGBColor(70, 70, 70),
+            marker: RGBColor(240, 240, 240),
+            primary: RGBColor(86, 156, 214),
+            secondary: RGBColor(244, 113, 116),
+            palette: vec![
+                RGBColor(78, 156, 255),
+                RGBColor(255, 159, 67),
+                RGBColor(46, 213, 115),
+                RGBColor(255, 107, 129),
+                RGBColor(162, 155, 254),
+                RGBColor(254, 202, 87),
+                RGBColor(72, 219, 251),
+                RGBColor(255, 159, 243),
+            ],
+            ..Theme::light()
+        }
+    }
+
+    /// Greys only, so that charts stay readable on a black and white printer.
+    pub fn print() -> Self {
+        Theme {
+            name: "print".to_string(),
+            grid: RGBColor(220, 220, 220),
+            primary: RGBColor(110, 110, 110),
+            secondary: RGBColor(30, 30, 30),
+            palette: vec![
+                RGBColor(32, 32, 32),
+                RGBColor(96, 96, 96),
+                RGBColor(154, 154, 154),
+                RGBColor(200, 200, 200),
+                RGBColor(64, 64, 64),
+                RGBColor(128, 128, 128),
+                RGBColor(180, 180, 180),
+            ],
+            negative: RGBColor(150, 150, 150),
+            positive: RGBColor(40, 40, 40),
+            font: "serif".to_string(),
+            ..Theme::light()
+        }
+    }
+
+    /// The Okabe–Ito palette, which stays distinguishable with the common
+    /// forms of color blindness.
+    pub fn colorblind() -> Self {
+        Theme {
+            name: "colorblind".to_string(),
+            primary: RGBColor(0, 114, 178),
+            secondary: RGBColor(213, 94, 0),
+            palette: vec![
+                RGBColor(230, 159, 0),
+                RGBColor(86, 180, 233),
+                RGBColor(0, 158, 115),
+                RGBColor(240, 228, 66),
+                RGBColor(0, 114, 178),
+                RGBColor(213, 94, 0),
+                RGBColor(204, 121, 167),
+                RGBColor(0, 0, 0),
+            ],
+            negative: RGBColor(0, 114, 178),
+            positive: RGBColor(213, 94, 0),
+            ..Theme::light()
+        }
+    }
+
+    pub fn built_in(name: &str) -> Option<Theme> {
+        match name.to_ascii_lowercase().as_str() {
+            "light" => Some(Theme::light()),
+            "dark" => Some(Theme::dark()),
+            "print" => Some(Theme::print()),
+            "colorblind" | "colourblind" => Some(Theme::colorblind()),
+            _ => None,
+        }
+    }
+
+    /// A built-in theme by name, or a theme file read as TOML or YAML
+    /// depending on the extension.
+    pub fn load(name_or_path: &str) -> Result<Theme, PipelineError> {
+        if let Some(theme) = Theme::built_in(name_or_path) {
+            return Ok(theme);
+        }
+
+        let file_path = name_or_path;
+        let text =
+            fs::read_to_string(file_path).map_err(|err| PipelineError::io_at(file_path, err))?;
+        let invalid = |err: String| PipelineError::Parse(format!("{}: {}", file_path, err));
+        let file: ThemeFile = match theme_file_extension(file_path).as_deref() {
+            Some("toml") => toml::from_str(&text).map_err(|err| invalid(err.to_string()))?,
+            Some("yaml") | Some("yml") => {
+                serde_yaml::from_str(&text).map_err(|err| invalid(err.to_string()))?
+            }
+            _ => {
+                return Err(invalid(
+                    "expected a .toml, .yaml or .yml theme file".to_string(),
+                ))
+            }
+        };
+        file.into_theme(file_path)
+            .map_err(|err| PipelineError::Config(format!("{}: {}", file_path, err)))
+    }
+
+    /// The palette color for the `index`-th group or column.
+    pub fn color(&self, index: usize) -> RGBColor {
+        match self.palette.len() {
+            0 => self.primary,
+            count => self.palette[index % count],
+        }
+    }
+}
+
+/// Checks a `--theme` value for clap: a built-in name, or a path with a
+/// theme file extension that is read once the run starts.
+pub fn parse_theme_name(value: &str) -> Result<String, String> {
+    if Theme::built_in(value).is_some()
+        || matches!(
+            theme_file_extension(value).as_deref(),
+            Some("toml") | Some("yaml") | Some("yml")
+        )
+    {
+        Ok(value.to_string())
+    } else {
+        Err(format!(
+            "expected one of {} or a .toml, .yaml or .yml theme file, got '{}'",
+            BUILT_IN_THEMES.join(", "),
+            value
+        ))
+    }
+}
+
+fn theme_file_extension(file_path: &str) -> Option<String> {
+    Path::new(file_path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_ascii_lowercase())
+}
+
+// A theme file: a built-in theme to start from and the settings to change,
+// colors written as "#rrggbb"
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ThemeFile {
+    base: Option<String>,
+    background: Option<String>,
+    text: Option<String>,
+    axis: Option<String>,
+    grid: Option<String>,
+    marker: Option<String>,
+    primary: Option<String>,
+    secondary: Option<String>,
+    palette: Option<Vec<String>>,
+    negative: Option<String>,
+    positive: Option<String>,
+    font: Option<String>,
+    caption_size: Option<u32>,
+    label_size: Option<u32>,
+    captions: Option<bool>,
+    margin: Option<u32>,
+    label_area: Option<u32>,
+    size: Option<String>,
+}
+
+impl ThemeFile {
+    fn into_theme(self, file_path: &str) -> Result<Theme, String> {
+        let mut theme = match &self.base {
+            Some(base) => Theme::built_in(base).ok_or_else(|| {
+                format!(
+                    "unknown base theme '{}', expected one of {}",
+                    base,
+                    BUILT_IN_THEMES.join(", ")
+                )
+            })?,
+            None => Theme::light(),
+        };
+        theme.name = file_path.to_string();
+
+        for (value, color) in [
+            (&self.background, &mut theme.background),
+            (&self.text, &mut theme.text),
+            (&self.axis, &mut theme.axis),
+            (&self.grid, &mut theme.grid),
+            (&self.marker, &mut theme.marker),
+            (&self.primary, &mut theme.primary),
+            (&self.secondary, &mut theme.secondary),
+            (&self.negative, &mut theme.negative),
+            (&self.positive, &mut theme.positive),
+        ] {
+            if let Some(value) = value {
+                *color = parse_color(value)?;
+            }
+        }
+        if let Some(palette) = &self.palette {
+            theme.palette = palette
+                .iter()
+                .map(|value| parse_color(value))
+                .collect::<Result<_, _>>()?;
+        }
+
+        if let Some(font) = self.font {
+            theme.font = font;
+        }
+        for (value, size) in [
+            (self.caption_size, &mut theme.caption_size),
+            (self.label_size, &mut theme.label_size),
+        ] {
+            match value {
+                Some(0) => return Err("font sizes must be positive".to_string()),
+                Some(value) => *size = value,
+                None => {}
+            }
+        }
+        if let Some(captions) = self.captions {
+            theme.captions = captions;
+        }
+        if let Some(margin) = self.margin {
+            theme.margin = margin;
+        }
+        if let Some(label_area) = self.label_area {
+            theme.label_area = label_area;
+        }
+        if let Some(size) = &self.size {
+            theme.size = Some(parse_chart_size(size)?);
+        }
+
+        Ok(theme)
+    }
+}
+
+/// Parses a color written as `#rrggbb` or `#rgb`.
+pub fn parse_color(value: &str) -> Result<RGBColor, String> {
+    let invalid = || format!("expected a color such as #1f77b4, got '{}'", value);
+    let digits = value.trim().strip_prefix('#').ok_or_else(invalid)?;
+    // Byte offsets below only fall on character boundaries for ASCII
+    if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+    let channel = |text: &str| u8::from_str_radix(text, 16).map_err(|_| invalid());
+
+    match digits.len() {
+        6 => Ok(RGBColor(
+            channel(&digits[0..2])?,
+            channel(&digits[2..4])?,
+            channel(&digits[4..6])?,
+        )),
+        3 => Ok(RGBColor(
+            channel(&digits[0..1])? * 17,
+            channel(&digits[1..2])? * 17,
+            channel(&digits[2..3])? * 17,
+        )),
+        _ => Err(invalid()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_color_reads_long_and_short_forms() {
+        assert_eq!(parse_color("#1f77b4"), Ok(RGBColor(31, 119, 180)));
+        assert_eq!(parse_color(" #FfF "), Ok(RGBColor(255, 255, 255)));
+        assert_eq!(parse_color("#a0c"), Ok(RGBColor(170, 0, 204)));
+    }
+
+    #[test]
+    fn parse_color_rejects_anything_else() {
+        for value in [
+            "1f77b4", "#1f77b", "#12345g", "#ééé", "#€", "#a€bc", "#+1+2+3", "",
+        ] {
+            assert!(parse_color(value).is_err(), "{:?} was accepted", value);
+        }
+    }
+
+    #[test]
+    fn invalid_colors_in_theme_files_are_configuration_errors() {
+        let file_path = std::env::temp_dir().join(format!("theme-{}.toml", std::process::id()));
+        fs::write(&file_path, "base = \"dark\"\nprimary = \"#ééé\"\n").unwrap();
+        let result = Theme::load(file_path.to_str().unwrap());
+        fs::remove_file(&file_path).unwrap();
+
+        match result {
+            Err(PipelineError::Config(message)) => {
+                assert!(message.ends_with("expected a color such as #1f77b4, got '#ééé'"))
+            }
+            other => panic!("expected a configuration error, got {:?}", other),
+        }
+    }
+}
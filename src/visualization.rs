@@ -1,67 +1,1567 @@
 // src/visualization.rs
 
-use crate::data_processing::DataSummary;
+use crate::correlation::CorrelationMatrix;
+use crate::data_processing::{DataSummary, Pca};
+use crate::error::PipelineError;
+use crate::theme::Theme;
+use chrono::Local;
+use clap::ValueEnum;
+use plotters::coord::combinators::WithKeyPoints;
+use plotters::coord::types::{RangedCoordf64, RangedCoordi32};
+use plotters::coord::{CoordTranslate, Shift};
 use plotters::prelude::*;
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use polars::prelude::{DataFrame, DataType};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
 
-pub fn create_charts(summary: &DataSummary, column_name: &str) {
-    let output_path = format!("output/{}_summary_chart.svg", column_name);
+// Charts that place several shapes per column give each column a slot this
+// many x units wide on an integer axis
+const SLOT: i32 = 1000;
 
-    let root = SVGBackend::new(&output_path, (800, 600)).into_drawing_area();
-    root.fill(&WHITE).unwrap();
+/// How many bins a histogram uses: a fixed count or one of the classic rules.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BinRule {
+    Count(usize),
+    Sturges,
+    FreedmanDiaconis,
+    Scott,
+}
 
-    // Determine the maximum value for scaling the chart
-    let max_value = summary.mean.max(summary.variance) * 1.2;
+impl BinRule {
+    // Rules based on a bin width can explode on nearly constant data
+    const MAX_BINS: usize = 1000;
 
-    let mut chart = ChartBuilder::on(&root)
-        .caption(
-            format!("Statistical Summary of '{}'", column_name),
-            ("sans-serif", 40).into_font(),
-        )
-        .margin(10)
-        .set_left_and_bottom_label_area_size(50)
-        .build_cartesian_2d(0..3, 0.0..max_value)
-        .unwrap();
+    pub fn bin_count(&self, summary: &DataSummary) -> usize {
+        let n = summary.count as f64;
+        let range = summary.max - summary.min;
+
+        let width = match self {
+            BinRule::Count(bins) => return (*bins).max(1),
+            BinRule::Sturges => return n.log2().ceil() as usize + 1,
+            BinRule::FreedmanDiaconis => 2.0 * summary.iqr / n.cbrt(),
+            BinRule::Scott => 3.49 * summary.std_dev / n.cbrt(),
+        };
+
+        if width > 0.0 && range > 0.0 {
+            ((range / width).ceil() as usize).clamp(1, Self::MAX_BINS)
+        } else {
+            1
+        }
+    }
+}
+
+impl fmt::Display for BinRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BinRule::Count(bins) => write!(f, "{}", bins),
+            BinRule::Sturges => write!(f, "sturges"),
+            BinRule::FreedmanDiaconis => write!(f, "fd"),
+            BinRule::Scott => write!(f, "scott"),
+        }
+    }
+}
+
+impl FromStr for BinRule {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "sturges" => Ok(BinRule::Sturges),
+            "fd" | "freedman-diaconis" => Ok(BinRule::FreedmanDiaconis),
+            "scott" => Ok(BinRule::Scott),
+            count => match count.parse::<usize>() {
+                Ok(bins) if bins > 0 => Ok(BinRule::Count(bins)),
+                _ => Err(format!(
+                    "expected a positive bin count, 'sturges', 'fd' or 'scott', got '{}'",
+                    value
+                )),
+            },
+        }
+    }
+}
+
+/// The charts drawn per analyzed column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Chart {
+    /// Summary chart per column, plus the comparison or grouped chart
+    Summary,
+    Histogram,
+    Box,
+    Violin,
+}
+
+impl Chart {
+    pub fn all() -> Vec<Chart> {
+        vec![Chart::Summary, Chart::Histogram, Chart::Box, Chart::Violin]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Chart::Summary => "summary",
+            Chart::Histogram => "histogram",
+            Chart::Box => "box",
+            Chart::Violin => "violin",
+        }
+    }
+}
+
+/// File formats charts can be written in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChartFormat {
+    Svg,
+    Png,
+    Jpeg,
+    Bmp,
+}
+
+impl ChartFormat {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChartFormat::Svg => "SVG",
+            ChartFormat::Png => "PNG",
+            ChartFormat::Jpeg => "JPEG",
+            ChartFormat::Bmp => "BMP",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ChartFormat::Svg => "svg",
+            ChartFormat::Png => "png",
+            ChartFormat::Jpeg => "jpg",
+            ChartFormat::Bmp => "bmp",
+        }
+    }
+}
+
+/// Placeholders a chart name template can use.
+pub const NAME_PLACEHOLDERS: [&str; 4] = ["{dataset}", "{column}", "{chart}", "{timestamp}"];
+
+/// How charts are written: their file format, size, resolution and where
+/// they are saved.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChartOptions {
+    pub format: ChartFormat,
+    /// Width and height in points of 1/96 inch. The correlation heatmap is a
+    /// square as wide as the other charts.
+    pub size: (u32, u32),
+    /// Resolution of bitmap charts, which are `size * dpi / 96` pixels with
+    /// text, lines and margins scaled alike. SVG charts ignore it.
+    pub dpi: u32,
+    /// Directory the charts are saved in
+    pub output_dir: String,
+    /// File name of every chart before its extension, built from
+    /// `NAME_PLACEHOLDERS`
+    pub name_template: String,
+    /// Fills `{dataset}`, usually the stem of the input file
+    pub dataset: String,
+    /// Fills `{timestamp}`, the local time the options were created
+    pub timestamp: String,
+    /// Colors, fonts and spacing of every chart
+    pub theme: Theme,
+}
+
+impl Default for ChartOptions {
+    fn default() -> Self {
+        ChartOptions {
+            format: ChartFormat::Svg,
+            size: (800, 600),
+            dpi: 96,
+            output_dir: "output".to_string(),
+            name_template: "{column}_{chart}".to_string(),
+            dataset: String::new(),
+            timestamp: Local::now().format("%Y%m%d-%H%M%S").to_string(),
+            theme: Theme::default(),
+        }
+    }
+}
+
+impl ChartOptions {
+    /// Options from the chart settings of the CLI and of pipeline plot steps,
+    /// checking each one. `theme` is a built-in theme name or a theme file,
+    /// and without a size the theme's size or the default one is used.
+    pub fn new(
+        theme: &str,
+        format: ChartFormat,
+        size: Option<(u32, u32)>,
+        dpi: u32,
+        output_dir: &str,
+        name_template: &str,
+    ) -> Result<ChartOptions, PipelineError> {
+        let theme = Theme::load(theme)?;
+        let defaults = ChartOptions::default();
+        Ok(ChartOptions {
+            format,
+            size: size.or(theme.size).unwrap_or(defaults.size),
+            dpi: check_dpi(dpi).map_err(PipelineError::Config)?,
+            output_dir: output_dir.to_string(),
+            name_template: parse_name_template(name_template).map_err(PipelineError::Config)?,
+            theme,
+            ..defaults
+        })
+    }
+
+    /// Where `chart` is saved, for a single column or, with `None`, for the
+    /// charts covering every column.
+    pub fn path(&self, column: Option<&str>, chart: &str) -> String {
+        let file_name = format!(
+            "{}.{}",
+            self.file_name(column, chart),
+            self.format.extension()
+        );
+        Path::new(&self.output_dir)
+            .join(file_name)
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// Where the summary chart of `summary` is saved.
+    pub fn summary_chart_path(&self, summary: &DataSummary) -> String {
+        self.path(Some(&summary_name(summary)), "summary_chart")
+    }
+
+    /// Where the histogram of `summary` is saved.
+    pub fn histogram_path(&self, summary: &DataSummary) -> String {
+        self.path(Some(&summary_name(summary)), "histogram")
+    }
+
+    // The template with every placeholder replaced by its sanitized value.
+    // Empty values take one neighbouring separator with them, so that
+    // "{column}_{chart}" names the box plot "box_plot"
+    fn file_name(&self, column: Option<&str>, chart: &str) -> String {
+        const EMPTY: char = '\0';
+
+        let mut name = self.name_template.clone();
+        for (placeholder, value) in NAME_PLACEHOLDERS.iter().zip([
+            self.dataset.as_str(),
+            column.unwrap_or_default(),
+            chart,
+            self.timestamp.as_str(),
+        ]) {
+            let value = sanitize_file_name(value);
+            let value = if value.is_empty() {
+                EMPTY.to_string()
+            } else {
+                value
+            };
+            name = name.replace(placeholder, &value);
+        }
+
+        let is_separator = |c: char| matches!(c, '_' | '-' | '.');
+        let mut chars: Vec<char> = name.chars().collect();
+        while let Some(index) = chars.iter().position(|c| *c == EMPTY) {
+            chars.remove(index);
+            if chars.get(index).copied().is_some_and(is_separator) {
+                chars.remove(index);
+            } else if index > 0 && is_separator(chars[index - 1]) {
+                chars.remove(index - 1);
+            }
+        }
+        chars.into_iter().collect()
+    }
+
+    // Device pixels per point
+    fn scale(&self) -> f64 {
+        match self.format {
+            ChartFormat::Svg => 1.0,
+            _ => self.dpi as f64 / 96.0,
+        }
+    }
+
+    // A length in points as device pixels
+    fn px(&self, points: u32) -> u32 {
+        ((points as f64 * self.scale()).round() as u32).max(1)
+    }
+
+    fn pixels(&self, (width, height): (u32, u32)) -> (u32, u32) {
+        (self.px(width), self.px(height))
+    }
+
+    fn font(&self, points: u32) -> FontDesc<'_> {
+        (self.theme.font.as_str(), self.px(points)).into_font()
+    }
+
+    // Text in the theme's font and color
+    fn text(&self, points: u32) -> TextStyle<'_> {
+        self.font(points).color(&self.theme.text)
+    }
+
+    // A square legend marker centered on the legend entry
+    fn legend_box(&self, color: ShapeStyle) -> impl Fn((i32, i32)) -> Rectangle<(i32, i32)> {
+        let half = self.px(5) as i32;
+        move |(x, y)| Rectangle::new([(x, y - half), (x + 2 * half, y + half)], color)
+    }
+
+    // A short legend line centered on the legend entry
+    fn legend_line(&self, color: ShapeStyle) -> impl Fn((i32, i32)) -> PathElement<(i32, i32)> {
+        let width = self.px(10) as i32;
+        move |(x, y)| PathElement::new(vec![(x, y), (x + width, y)], color)
+    }
+}
+
+/// Parses a chart size written as `WIDTHxHEIGHT` in points, such as `1200x900`.
+pub fn parse_chart_size(value: &str) -> Result<(u32, u32), String> {
+    let size = value
+        .to_ascii_lowercase()
+        .split_once('x')
+        .and_then(|(width, height)| {
+            Some((
+                width.trim().parse::<u32>().ok()?,
+                height.trim().parse::<u32>().ok()?,
+            ))
+        });
+    match size {
+        Some((width, height))
+            if (100..=10_000).contains(&width) && (100..=10_000).contains(&height) =>
+        {
+            Ok((width, height))
+        }
+        _ => Err(format!(
+            "expected a size such as 800x600, each side between 100 and 10000, got '{}'",
+            value
+        )),
+    }
+}
+
+// The column a summary's own charts are named after, followed by the group
+// value for grouped summaries
+fn summary_name(summary: &DataSummary) -> String {
+    match &summary.group {
+        Some(group) => format!("{}_{}", summary.column, group.value),
+        None => summary.column.clone(),
+    }
+}
+
+// Fails when two summaries would save their own charts to the same file,
+// e.g. columns "a b" and "a/b" that are both sanitized to "a_b"
+fn check_distinct_paths(
+    summaries: &[DataSummary],
+    options: &ChartOptions,
+) -> Result<(), PipelineError> {
+    let mut seen: HashMap<String, &DataSummary> = HashMap::new();
+    for summary in summaries {
+        let path = options.summary_chart_path(summary);
+        match seen.get(&path) {
+            Some(other) if other.label() != summary.label() => {
+                return Err(PipelineError::Config(format!(
+                    "the charts of '{}' and '{}' would both be saved as {}, give one of the \
+                     columns another name with --derive",
+                    other.label(),
+                    summary.label(),
+                    path
+                )))
+            }
+            _ => {
+                seen.insert(path, summary);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Replaces everything but letters, digits, `-` and `_` with `_`, so that a
+/// column or file name can be part of a file name on any platform, and keeps
+/// at most 100 characters.
+pub fn sanitize_file_name(value: &str) -> String {
+    value
+        .chars()
+        .take(100)
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Checks a chart name template: only known placeholders, and `{column}` and
+/// `{chart}` so that no two charts of a run share a name.
+pub fn parse_name_template(template: &str) -> Result<String, String> {
+    let mut rest = template.to_string();
+    for placeholder in NAME_PLACEHOLDERS {
+        rest = rest.replace(placeholder, "");
+    }
+    if let Some(c) = rest
+        .chars()
+        .find(|c| !(c.is_alphanumeric() || matches!(c, '-' | '_' | '.')))
+    {
+        return Err(format!(
+            "unexpected '{}' in the name template '{}', use letters, digits, '-', '_', '.' and the placeholders {}",
+            c,
+            template,
+            NAME_PLACEHOLDERS.join(", ")
+        ));
+    }
+    if !template.contains("{column}") || !template.contains("{chart}") {
+        return Err(format!(
+            "the name template '{}' needs both {{column}} and {{chart}}",
+            template
+        ));
+    }
+    Ok(template.to_string())
+}
+
+/// Checks a bitmap resolution, which must lie between 24 and 1200 dpi.
+pub fn check_dpi(dpi: u32) -> Result<u32, String> {
+    if (24..=1200).contains(&dpi) {
+        Ok(dpi)
+    } else {
+        Err(format!(
+            "expected a resolution between 24 and 1200 dpi, got {}",
+            dpi
+        ))
+    }
+}
+
+// Presents the drawing explicitly so that write failures are not lost on drop
+macro_rules! render {
+    ($options:expr, $path:expr, $size:expr, $draw:expr) => {{
+        let size = $options.pixels($size);
+        match $options.format {
+            ChartFormat::Svg => {
+                let root = SVGBackend::new($path, size).into_drawing_area();
+                root.fill(&$options.theme.background)?;
+                ($draw)(&root)?;
+                root.present()?;
+            }
+            ChartFormat::Png | ChartFormat::Jpeg | ChartFormat::Bmp => {
+                let root = BitMapBackend::new($path, size).into_drawing_area();
+                root.fill(&$options.theme.background)?;
+                ($draw)(&root)?;
+                root.present()?;
+            }
+        }
+    }};
+}
+
+// Starts `$chart`'s mesh with the theme's tick labels, axis descriptions,
+// axes and grid, ready for the chart's own settings
+macro_rules! themed_mesh {
+    ($chart:expr, $options:expr) => {
+        $chart
+            .configure_mesh()
+            .label_style($options.text($options.theme.label_size))
+            .axis_desc_style($options.text($options.theme.label_size))
+            .axis_style($options.theme.axis)
+            .bold_line_style($options.theme.grid)
+            .light_line_style($options.theme.grid.mix(0.5))
+            .set_all_tick_mark_size($options.px(5))
+    };
+}
+
+// A chart on `root` with the theme's margin and label areas, and `caption`
+// above it unless the theme leaves captions out
+fn chart_builder<'a, 'b, DB: DrawingBackend>(
+    root: &'a DrawingArea<DB, Shift>,
+    caption: &str,
+    options: &'b ChartOptions,
+) -> ChartBuilder<'a, 'b, DB> {
+    let mut builder = ChartBuilder::on(root);
+    builder
+        .margin(options.px(options.theme.margin))
+        .set_left_and_bottom_label_area_size(options.px(options.theme.label_area));
+    if options.theme.captions {
+        builder.caption(caption, options.text(options.theme.caption_size));
+    }
+    builder
+}
+
+/// Draws `charts` into the output directory and returns the paths written.
+/// Only the summary charts can be drawn without the rows in `frame`.
+pub fn draw_charts(
+    charts: &[Chart],
+    frame: Option<&DataFrame>,
+    summaries: &[DataSummary],
+    bins: BinRule,
+    options: &ChartOptions,
+) -> Result<Vec<String>, PipelineError> {
+    let mut paths = Vec::new();
+    for chart in charts {
+        let rows = || {
+            frame.ok_or_else(|| {
+                PipelineError::EmptyData(format!(
+                    "the {} chart needs the rows, not only their summary",
+                    chart.name()
+                ))
+            })
+        };
+
+        match chart {
+            Chart::Summary => paths.extend(create_charts(summaries, options)?),
+            Chart::Histogram => paths.extend(create_histograms(rows()?, summaries, bins, options)?),
+            Chart::Box => paths.push(create_box_plot(rows()?, summaries, options)?),
+            Chart::Violin => paths.push(create_violin_plot(rows()?, summaries, options)?),
+        }
+    }
 
+    Ok(paths)
+}
+
+pub fn create_charts(
+    summaries: &[DataSummary],
+    options: &ChartOptions,
+) -> Result<Vec<String>, PipelineError> {
+    check_distinct_paths(summaries, options)?;
+
+    let mut paths = summaries
+        .iter()
+        .map(|summary| create_summary_chart(summary, options))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Grouped summaries are compared per column, plain ones only when there
+    // is more than one column
+    if summaries.iter().any(|summary| summary.group.is_some()) {
+        paths.push(create_grouped_chart(summaries, options)?);
+    } else if summaries.len() > 1 {
+        paths.push(create_comparison_chart(summaries, options)?);
+    }
+
+    Ok(paths)
+}
+
+fn create_summary_chart(
+    summary: &DataSummary,
+    options: &ChartOptions,
+) -> Result<String, PipelineError> {
+    let output_path = options.summary_chart_path(summary);
+    render!(options, &output_path, options.size, |root| {
+        draw_summary_chart(root, summary, options)
+    });
+    Ok(output_path)
+}
+
+fn draw_summary_chart<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    summary: &DataSummary,
+    options: &ChartOptions,
+) -> Result<(), PipelineError> {
+    let caption = format!("Statistical Summary of '{}'", summary.label());
+    let area = if options.theme.captions {
+        root.titled(&caption, options.text(options.theme.caption_size))?
+    } else {
+        root.clone()
+    };
+
+    // One panel per statistic, each with its own axis: the variance is in
+    // squared units and would dwarf the mean on a shared one
+    let panels = area.split_evenly((1, 3));
+    let statistics = [
+        ("Mean", summary.mean, Some(summary.std_dev), "Value"),
+        ("Std dev", summary.std_dev, None, "Value"),
+        ("Variance", summary.variance, None, "Squared units"),
+    ];
+    for (panel, (name, value, spread, unit)) in panels.iter().zip(statistics) {
+        let color = if spread.is_some() {
+            options.theme.secondary
+        } else {
+            options.theme.primary
+        };
+        draw_statistic_panel(panel, name, value, spread, unit, color, options)?;
+    }
+
+    Ok(())
+}
+
+// A bar from zero to `value`, pointing down for negative values, with one
+// `spread` either way as an error bar when given
+fn draw_statistic_panel<DB: DrawingBackend>(
+    panel: &DrawingArea<DB, Shift>,
+    name: &str,
+    value: f64,
+    spread: Option<f64>,
+    unit: &str,
+    color: RGBColor,
+    options: &ChartOptions,
+) -> Result<(), PipelineError> {
+    let extent = match spread {
+        Some(spread) if spread.is_finite() => vec![value - spread, value + spread],
+        _ => vec![value],
+    };
+
+    let mut builder = ChartBuilder::on(panel);
+    builder
+        .margin(options.px(options.theme.margin))
+        .set_label_area_size(
+            LabelAreaPosition::Left,
+            options.px(options.theme.label_area),
+        );
+    if options.theme.captions {
+        builder.caption(
+            format!("{}: {}", name, format_statistic(value)),
+            options.text(options.theme.label_size * 3 / 2),
+        );
+    }
+    let mut chart = builder.build_cartesian_2d(0.0..1.0, value_range(&extent))?;
+
+    themed_mesh!(chart, options)
+        .disable_x_mesh()
+        .disable_x_axis()
+        .y_desc(unit)
+        .y_label_formatter(&|y| format_tick(*y))
+        .draw()?;
+
+    // Zero stays visible as the baseline of the bar
+    chart.draw_series(std::iter::once(PathElement::new(
+        vec![(0.0, 0.0), (1.0, 0.0)],
+        options.theme.axis.stroke_width(options.px(1)),
+    )))?;
+
+    if value.is_finite() {
+        chart.draw_series(std::iter::once(Rectangle::new(
+            [(0.25, 0.0), (0.75, value)],
+            color.filled(),
+        )))?;
+    }
+    if let Some(spread) = spread.filter(|spread| value.is_finite() && *spread > 0.0) {
+        chart.draw_series(std::iter::once(ErrorBar::new_vertical(
+            0.5,
+            value - spread,
+            value,
+            value + spread,
+            options.theme.marker.stroke_width(options.px(2)),
+            options.px(20),
+        )))?;
+    }
+
+    Ok(())
+}
+
+// A value axis that always includes zero, padded by a tenth of its span on
+// the sides holding values. A statistic of zero gets a unit axis rather than
+// an empty one
+fn value_range(values: &[f64]) -> std::ops::Range<f64> {
+    let finite = values.iter().copied().filter(|value| value.is_finite());
+    let low = finite.clone().fold(0.0, f64::min);
+    let high = finite.fold(0.0, f64::max);
+    if low == high {
+        return 0.0..1.0;
+    }
+
+    let padding = (high - low) * 0.1;
+    let low = if low < 0.0 { low - padding } else { low };
+    let high = if high > 0.0 { high + padding } else { high };
+    low..high
+}
+
+// Four significant digits, in scientific notation when very large or small
+fn format_statistic(value: f64) -> String {
+    if !value.is_finite() {
+        return "n/a".to_string();
+    }
+    if value == 0.0 {
+        return "0".to_string();
+    }
+
+    let magnitude = value.abs().log10().floor() as i32;
+    if !(-3..6).contains(&magnitude) {
+        format!("{:.3e}", value)
+    } else {
+        format!("{:.*}", (3 - magnitude).max(0) as usize, value)
+    }
+}
+
+// Like `format_statistic` without trailing zeros, so that ticks stay narrow
+fn format_tick(value: f64) -> String {
+    let trim = |text: &str| {
+        if text.contains('.') {
+            text.trim_end_matches('0').trim_end_matches('.').to_string()
+        } else {
+            text.to_string()
+        }
+    };
+
+    let text = format_statistic(value);
+    match text.split_once('e') {
+        Some((mantissa, exponent)) => format!("{}e{}", trim(mantissa), exponent),
+        None => trim(&text),
+    }
+}
+
+// The series labels of `chart` in a bordered box
+fn draw_legend<'a, DB: DrawingBackend + 'a, CT: CoordTranslate>(
+    chart: &mut ChartContext<'a, DB, CT>,
+    options: &ChartOptions,
+    position: SeriesLabelPosition,
+) -> Result<(), PipelineError> {
     chart
-        .configure_mesh()
-        .disable_mesh()
-        .x_labels(2)
-        .x_desc("Statistic")
-        .y_desc("Value")
-        .x_label_formatter(&|x| match *x {
-            1 => "Mean".to_string(),
-            2 => "Variance".to_string(),
+        .configure_series_labels()
+        .position(position)
+        .label_font(options.text(options.theme.label_size))
+        .legend_area_size(options.px(30))
+        .margin(options.px(10))
+        .background_style(options.theme.background)
+        .border_style(options.theme.axis)
+        .draw()?;
+    Ok(())
+}
+
+/// Draws the mean of every column side by side, with one standard deviation
+/// either way as an error bar.
+fn create_comparison_chart(
+    summaries: &[DataSummary],
+    options: &ChartOptions,
+) -> Result<String, PipelineError> {
+    let output_path = options.path(None, "comparison_chart");
+    render!(options, &output_path, options.size, |root| {
+        draw_comparison_chart(root, summaries, options)
+    });
+    Ok(output_path)
+}
+
+fn draw_comparison_chart<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    summaries: &[DataSummary],
+    options: &ChartOptions,
+) -> Result<(), PipelineError> {
+    // Keep zero on the axis so bars for negative means stay visible
+    let low = summaries
+        .iter()
+        .map(|summary| summary.mean - summary.std_dev)
+        .fold(0.0, f64::min);
+    let high = summaries
+        .iter()
+        .map(|summary| summary.mean + summary.std_dev)
+        .fold(0.0, f64::max);
+    let padding = (high - low).max(1.0) * 0.1;
+
+    // Integer ranges include their end, so this is one segment per column
+    let mut chart = chart_builder(root, "Column Comparison", options).build_cartesian_2d(
+        (0..summaries.len().saturating_sub(1)).into_segmented(),
+        (low - padding)..(high + padding),
+    )?;
+
+    themed_mesh!(chart, options)
+        .disable_x_mesh()
+        .x_desc("Column")
+        .y_desc("Mean ± std dev")
+        .x_label_formatter(&|x| match x {
+            SegmentValue::CenterOf(index) => summaries
+                .get(*index)
+                .map(|summary| summary.column.clone())
+                .unwrap_or_default(),
             _ => "".to_string(),
         })
-        .draw()
-        .unwrap();
+        .draw()?;
 
-    // Plot mean as a bar
     chart
-        .draw_series(std::iter::once(Rectangle::new(
-            [(1 - 0.25, 0.0), (1 + 0.25, summary.mean)],
-            RED.filled(),
-        )))
-        .unwrap()
+        .draw_series(
+            Histogram::vertical(&chart)
+                .style(options.theme.secondary.filled())
+                .margin(options.px(20))
+                .data(summaries.iter().enumerate().map(|(i, s)| (i, s.mean))),
+        )?
         .label("Mean")
-        .legend(|(x, y)| Rectangle::new([(x, y - 5), (x + 10, y + 5)], RED.filled()));
+        .legend(options.legend_box(options.theme.secondary.filled()));
 
-    // Plot variance as a bar
     chart
-        .draw_series(std::iter::once(Rectangle::new(
-            [(2 - 0.25, 0.0), (2 + 0.25, summary.variance)],
-            BLUE.filled(),
-        )))
-        .unwrap()
-        .label("Variance")
-        .legend(|(x, y)| Rectangle::new([(x, y - 5), (x + 10, y + 5)], BLUE.filled()));
-
-    // Draw the legend
+        .draw_series(summaries.iter().enumerate().map(|(i, summary)| {
+            ErrorBar::new_vertical(
+                SegmentValue::CenterOf(i),
+                summary.mean - summary.std_dev,
+                summary.mean,
+                summary.mean + summary.std_dev,
+                options.theme.marker.stroke_width(options.px(2)),
+                options.px(20),
+            )
+        }))?
+        .label("± 1 std dev")
+        .legend(options.legend_line(options.theme.marker.stroke_width(options.px(1))));
+
+    draw_legend(&mut chart, options, SeriesLabelPosition::UpperLeft)
+}
+
+/// Draws the mean of every group as a cluster of bars per column, with one
+/// standard deviation either way as an error bar.
+fn create_grouped_chart(
+    summaries: &[DataSummary],
+    options: &ChartOptions,
+) -> Result<String, PipelineError> {
+    let output_path = options.path(None, "grouped_chart");
+    render!(options, &output_path, options.size, |root| {
+        draw_grouped_chart(root, summaries, options)
+    });
+    Ok(output_path)
+}
+
+fn draw_grouped_chart<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    summaries: &[DataSummary],
+    options: &ChartOptions,
+) -> Result<(), PipelineError> {
+    // Columns and group values in order of first appearance
+    let mut columns: Vec<&str> = Vec::new();
+    let mut groups: Vec<&str> = Vec::new();
+    for summary in summaries {
+        if !columns.contains(&summary.column.as_str()) {
+            columns.push(&summary.column);
+        }
+        if let Some(group) = &summary.group {
+            if !groups.contains(&group.value.as_str()) {
+                groups.push(&group.value);
+            }
+        }
+    }
+    let group_column = summaries
+        .iter()
+        .find_map(|summary| summary.group.as_ref())
+        .map(|group| group.column.clone())
+        .unwrap_or_default();
+
+    // Keep zero on the axis so bars for negative means stay visible
+    let low = summaries
+        .iter()
+        .map(|summary| summary.mean - summary.std_dev)
+        .fold(0.0, f64::min);
+    let high = summaries
+        .iter()
+        .map(|summary| summary.mean + summary.std_dev)
+        .fold(0.0, f64::max);
+    let padding = (high - low).max(1.0) * 0.1;
+
+    let slot_count = columns.len() as i32;
+    let slots = (0..slot_count * SLOT)
+        .with_key_points((0..slot_count).map(|i| i * SLOT + SLOT / 2).collect());
+
+    let mut chart = chart_builder(
+        root,
+        &format!("Column Comparison by '{}'", group_column),
+        options,
+    )
+    .build_cartesian_2d(slots, (low - padding)..(high + padding))?;
+
+    themed_mesh!(chart, options)
+        .disable_x_mesh()
+        .x_desc("Column")
+        .y_desc("Mean ± std dev")
+        .x_label_formatter(&|x| {
+            columns
+                .get((*x / SLOT) as usize)
+                .map(|column| column.to_string())
+                .unwrap_or_default()
+        })
+        .draw()?;
+
+    // Bars of a cluster share 80% of the slot
+    let bar_width = SLOT * 8 / 10 / groups.len().max(1) as i32;
+    for (group_index, group_value) in groups.iter().enumerate() {
+        let color = options.theme.color(group_index);
+        let bars: Vec<(i32, &DataSummary)> = summaries
+            .iter()
+            .filter(|summary| {
+                summary.group.as_ref().map(|group| group.value.as_str()) == Some(*group_value)
+            })
+            .map(|summary| {
+                let column_index = columns.iter().position(|c| *c == summary.column).unwrap();
+                let left = column_index as i32 * SLOT + SLOT / 10 + group_index as i32 * bar_width;
+                (left, summary)
+            })
+            .collect();
+
+        chart
+            .draw_series(bars.iter().map(|(left, summary)| {
+                let mut bar = Rectangle::new(
+                    [(*left, 0.0), (left + bar_width, summary.mean)],
+                    color.filled(),
+                );
+                bar.set_margin(0, 0, 1, 1);
+                bar
+            }))?
+            .label(group_value.to_string())
+            .legend(options.legend_box(color.filled()));
+
+        chart.draw_series(bars.iter().map(|(left, summary)| {
+            ErrorBar::new_vertical(
+                left + bar_width / 2,
+                summary.mean - summary.std_dev,
+                summary.mean,
+                summary.mean + summary.std_dev,
+                options.theme.marker.stroke_width(options.px(2)),
+                options.px(10),
+            )
+        }))?;
+    }
+
+    draw_legend(&mut chart, options, SeriesLabelPosition::UpperLeft)
+}
+
+/// Draws a histogram of every summarized column in `frame` and returns the
+/// paths written.
+pub fn create_histograms(
+    frame: &DataFrame,
+    summaries: &[DataSummary],
+    rule: BinRule,
+    options: &ChartOptions,
+) -> Result<Vec<String>, PipelineError> {
+    check_distinct_paths(summaries, options)?;
+
+    summaries
+        .iter()
+        .map(|summary| {
+            let values = column_values(frame, summary)?;
+            create_histogram(&values, summary, rule.bin_count(summary), options)
+        })
+        .collect()
+}
+
+// Non-null values behind a summary as f64, restricted to its group if it has
+// one, in ascending order
+fn column_values(frame: &DataFrame, summary: &DataSummary) -> Result<Vec<f64>, PipelineError> {
+    let values = frame.column(&summary.column)?.cast(&DataType::Float64)?;
+    let values = values.f64()?;
+
+    let mut values: Vec<f64> = match &summary.group {
+        Some(group) => {
+            let keys = frame.column(&group.column)?.cast(&DataType::Utf8)?;
+            keys.utf8()?
+                .into_iter()
+                .zip(values)
+                .filter(|(key, _)| key.unwrap_or("null") == group.value)
+                .filter_map(|(_, value)| value)
+                .collect()
+        }
+        None => values.into_iter().flatten().collect(),
+    };
+    values.sort_by(|a, b| a.total_cmp(b));
+    Ok(values)
+}
+
+fn create_histogram(
+    values: &[f64],
+    summary: &DataSummary,
+    bins: usize,
+    options: &ChartOptions,
+) -> Result<String, PipelineError> {
+    let output_path = options.histogram_path(summary);
+    render!(options, &output_path, options.size, |root| {
+        draw_histogram(root, values, summary, bins, options)
+    });
+    Ok(output_path)
+}
+
+fn draw_histogram<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    values: &[f64],
+    summary: &DataSummary,
+    bins: usize,
+    options: &ChartOptions,
+) -> Result<(), PipelineError> {
+    let column_name = summary.label();
+
+    // Give constant columns a unit-wide range so the single bin is visible
+    let (low, high) = if summary.max > summary.min {
+        (summary.min, summary.max)
+    } else {
+        (summary.min - 0.5, summary.max + 0.5)
+    };
+    let width = (high - low) / bins as f64;
+
+    let mut counts = vec![0usize; bins];
+    for value in values {
+        // The maximum belongs to the last bin rather than one past it
+        let bin = (((value - low) / width) as usize).min(bins - 1);
+        counts[bin] += 1;
+    }
+    let max_count = counts.iter().copied().max().unwrap_or(0);
+
+    let mut chart = chart_builder(
+        root,
+        &format!("Distribution of '{}' ({} bins)", column_name, bins),
+        options,
+    )
+    .build_cartesian_2d(low..high, 0..(max_count + max_count / 10 + 1))?;
+
+    themed_mesh!(chart, options)
+        .disable_x_mesh()
+        .x_desc(column_name.as_str())
+        .y_desc("Count")
+        .draw()?;
+
+    chart.draw_series(counts.iter().enumerate().map(|(bin, count)| {
+        let start = low + bin as f64 * width;
+        let mut bar = Rectangle::new(
+            [(start, 0), (start + width, *count)],
+            options.theme.primary.filled(),
+        );
+        bar.set_margin(0, 0, 1, 1);
+        bar
+    }))?;
+
+    Ok(())
+}
+
+/// Draws one box per column on a shared axis. Boxes span Q1 to Q3 with a line
+/// at the median; whiskers reach the most extreme values within 1.5 IQR of the
+/// box and anything beyond them is drawn as an outlier.
+pub fn create_box_plot(
+    frame: &DataFrame,
+    summaries: &[DataSummary],
+    options: &ChartOptions,
+) -> Result<String, PipelineError> {
+    let output_path = options.path(None, "box_plot");
+    render!(options, &output_path, options.size, |root| {
+        draw_box_plot(root, frame, summaries, options)
+    });
+    Ok(output_path)
+}
+
+fn draw_box_plot<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    frame: &DataFrame,
+    summaries: &[DataSummary],
+    options: &ChartOptions,
+) -> Result<(), PipelineError> {
+    let mut chart = distribution_chart(root, "Box Plot", summaries, options)?;
+
+    for (index, summary) in summaries.iter().enumerate() {
+        let values = column_values(frame, summary)?;
+        let color = options.theme.color(index);
+        let center = index as i32 * SLOT + SLOT / 2;
+
+        // Tukey fences
+        let lower_fence = summary.q1 - 1.5 * summary.iqr;
+        let upper_fence = summary.q3 + 1.5 * summary.iqr;
+        let low_whisker = values
+            .iter()
+            .copied()
+            .find(|value| *value >= lower_fence)
+            .unwrap_or(summary.q1);
+        let high_whisker = values
+            .iter()
+            .rev()
+            .copied()
+            .find(|value| *value <= upper_fence)
+            .unwrap_or(summary.q3);
+
+        let corners = [(center - 300, summary.q1), (center + 300, summary.q3)];
+        chart.draw_series([
+            Rectangle::new(corners, color.mix(0.3).filled()),
+            Rectangle::new(corners, color.stroke_width(options.px(2))),
+        ])?;
+
+        let thin = options.theme.marker.stroke_width(options.px(1));
+        chart.draw_series([
+            PathElement::new(
+                vec![
+                    (center - 300, summary.median),
+                    (center + 300, summary.median),
+                ],
+                options.theme.marker.stroke_width(options.px(3)),
+            ),
+            PathElement::new(vec![(center, summary.q3), (center, high_whisker)], thin),
+            PathElement::new(vec![(center, summary.q1), (center, low_whisker)], thin),
+            PathElement::new(
+                vec![(center - 150, high_whisker), (center + 150, high_whisker)],
+                thin,
+            ),
+            PathElement::new(
+                vec![(center - 150, low_whisker), (center + 150, low_whisker)],
+                thin,
+            ),
+        ])?;
+
+        chart.draw_series(
+            values
+                .iter()
+                .filter(|value| **value < lower_fence || **value > upper_fence)
+                .map(|value| {
+                    Circle::new(
+                        (center, *value),
+                        options.px(3),
+                        color.stroke_width(options.px(1)),
+                    )
+                }),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Draws one violin per column on a shared axis: a Gaussian kernel density
+/// estimate mirrored around the column's center, with the interquartile range
+/// and the median marked inside.
+pub fn create_violin_plot(
+    frame: &DataFrame,
+    summaries: &[DataSummary],
+    options: &ChartOptions,
+) -> Result<String, PipelineError> {
+    let output_path = options.path(None, "violin_plot");
+    render!(options, &output_path, options.size, |root| {
+        draw_violin_plot(root, frame, summaries, options)
+    });
+    Ok(output_path)
+}
+
+fn draw_violin_plot<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    frame: &DataFrame,
+    summaries: &[DataSummary],
+    options: &ChartOptions,
+) -> Result<(), PipelineError> {
+    let mut chart = distribution_chart(root, "Violin Plot", summaries, options)?;
+
+    for (index, summary) in summaries.iter().enumerate() {
+        let values = column_values(frame, summary)?;
+        let color = options.theme.color(index);
+        let center = index as i32 * SLOT + SLOT / 2;
+
+        // Evaluate the density over the observed range and scale its peak to
+        // the violin's half-width
+        let steps = 100;
+        let points: Vec<f64> = (0..=steps)
+            .map(|step| summary.min + (summary.max - summary.min) * step as f64 / steps as f64)
+            .collect();
+        let density = kernel_density(&values, silverman_bandwidth(summary), &points);
+        let peak = density.iter().copied().fold(0.0, f64::max);
+        let scale = if peak > 0.0 { 400.0 / peak } else { 0.0 };
+
+        let outline: Vec<(i32, f64)> = points
+            .iter()
+            .zip(&density)
+            .map(|(y, d)| (center + (d * scale) as i32, *y))
+            .chain(
+                points
+                    .iter()
+                    .zip(&density)
+                    .rev()
+                    .map(|(y, d)| (center - (d * scale) as i32, *y)),
+            )
+            .collect();
+
+        chart.draw_series(std::iter::once(Polygon::new(
+            outline.clone(),
+            color.mix(0.4).filled(),
+        )))?;
+        chart.draw_series(std::iter::once(PathElement::new(
+            outline,
+            color.stroke_width(options.px(2)),
+        )))?;
+
+        chart.draw_series([
+            Rectangle::new(
+                [(center - 20, summary.q1), (center + 20, summary.q3)],
+                options.theme.marker.filled(),
+            ),
+            Rectangle::new(
+                [(center - 50, summary.median), (center + 50, summary.median)],
+                options.theme.background.stroke_width(options.px(3)),
+            ),
+        ])?;
+    }
+
+    Ok(())
+}
+
+type DistributionChart<'a, DB> =
+    ChartContext<'a, DB, Cartesian2d<WithKeyPoints<RangedCoordi32>, RangedCoordf64>>;
+
+// Axes shared by the box and violin plots: one slot per column, labelled at
+// its center, and a value range covering every column
+fn distribution_chart<'a, DB: DrawingBackend>(
+    root: &'a DrawingArea<DB, Shift>,
+    caption: &str,
+    summaries: &'a [DataSummary],
+    options: &ChartOptions,
+) -> Result<DistributionChart<'a, DB>, PipelineError> {
+    let low = summaries
+        .iter()
+        .map(|summary| summary.min)
+        .fold(f64::INFINITY, f64::min);
+    let high = summaries
+        .iter()
+        .map(|summary| summary.max)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let padding = if high > low { (high - low) * 0.05 } else { 0.5 };
+
+    let columns = summaries.len() as i32;
+    let slots =
+        (0..columns * SLOT).with_key_points((0..columns).map(|i| i * SLOT + SLOT / 2).collect());
+
+    let mut chart = chart_builder(root, caption, options)
+        .build_cartesian_2d(slots, (low - padding)..(high + padding))?;
+
+    themed_mesh!(chart, options)
+        .disable_x_mesh()
+        .x_desc("Column")
+        .y_desc("Value")
+        .x_label_formatter(&|x| {
+            summaries
+                .get((*x / SLOT) as usize)
+                .map(DataSummary::label)
+                .unwrap_or_default()
+        })
+        .draw()?;
+
+    Ok(chart)
+}
+
+// Silverman's rule of thumb, falling back to the standard deviation when the
+// IQR is zero and to 1 for constant columns
+fn silverman_bandwidth(summary: &DataSummary) -> f64 {
+    let spread = if summary.iqr > 0.0 {
+        summary.std_dev.min(summary.iqr / 1.34)
+    } else {
+        summary.std_dev
+    };
+    let bandwidth = 0.9 * spread * (summary.count as f64).powf(-0.2);
+    if bandwidth > 0.0 {
+        bandwidth
+    } else {
+        1.0
+    }
+}
+
+// Gaussian kernel density of the sorted `values` at each of `points`. Values
+// more than four bandwidths away contribute nothing measurable and are skipped.
+fn kernel_density(values: &[f64], bandwidth: f64, points: &[f64]) -> Vec<f64> {
+    let norm = 1.0 / (values.len() as f64 * bandwidth * (2.0 * std::f64::consts::PI).sqrt());
+
+    points
+        .iter()
+        .map(|point| {
+            let start = values.partition_point(|value| *value < point - 4.0 * bandwidth);
+            let end = values.partition_point(|value| *value <= point + 4.0 * bandwidth);
+            let sum: f64 = values[start..end]
+                .iter()
+                .map(|value| {
+                    let z = (point - value) / bandwidth;
+                    (-0.5 * z * z).exp()
+                })
+                .sum();
+            sum * norm
+        })
+        .collect()
+}
+
+/// Draws a correlation matrix as a heatmap, with every cell annotated with its
+/// coefficient and stars for its significance.
+pub fn create_correlation_heatmap(
+    matrix: &CorrelationMatrix,
+    options: &ChartOptions,
+) -> Result<String, PipelineError> {
+    let chart = format!(
+        "{}_correlation_heatmap",
+        matrix.method.name().to_lowercase()
+    );
+    if matrix.columns.is_empty() {
+        return Err(PipelineError::EmptyData(
+            "the correlation heatmap needs at least one column".to_string(),
+        ));
+    }
+
+    let output_path = options.path(None, &chart);
+    let side = options.size.0;
+    render!(options, &output_path, (side, side), |root| {
+        draw_correlation_heatmap(root, matrix, options)
+    });
+    Ok(output_path)
+}
+
+fn draw_correlation_heatmap<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    matrix: &CorrelationMatrix,
+    options: &ChartOptions,
+) -> Result<(), PipelineError> {
+    let size = matrix.columns.len();
+
+    let mut chart = chart_builder(
+        root,
+        &format!("{} Correlation", matrix.method.name()),
+        options,
+    )
+    .set_label_area_size(LabelAreaPosition::Left, options.px(100))
+    .set_label_area_size(LabelAreaPosition::Bottom, options.px(60))
+    // Integer ranges include their end, so this is one segment per column
+    .build_cartesian_2d(
+        (0..size - 1).into_segmented(),
+        (0..size - 1).into_segmented(),
+    )?;
+
+    // The first column is drawn in the top row
+    let column_label = |value: &SegmentValue<usize>, flip: bool| match value {
+        SegmentValue::CenterOf(index) if *index < size => {
+            let index = if flip { size - 1 - index } else { *index };
+            matrix.columns[index].clone()
+        }
+        _ => "".to_string(),
+    };
+
+    themed_mesh!(chart, options)
+        .disable_mesh()
+        .x_desc("* p < 0.05    ** p < 0.01    *** p < 0.001")
+        .x_labels(size)
+        .y_labels(size)
+        .x_label_formatter(&|x| column_label(x, false))
+        .y_label_formatter(&|y| column_label(y, true))
+        .draw()?;
+
+    let cells: Vec<(usize, usize)> = (0..size)
+        .flat_map(|row| (0..size).map(move |column| (row, column)))
+        .collect();
+
+    chart.draw_series(cells.iter().map(|&(row, column)| {
+        let y = size - 1 - row;
+        Rectangle::new(
+            [
+                (SegmentValue::Exact(column), SegmentValue::Exact(y)),
+                (SegmentValue::Exact(column + 1), SegmentValue::Exact(y + 1)),
+            ],
+            diverging_color(matrix.coefficients[row][column], &options.theme).filled(),
+        )
+    }))?;
+
+    let font_size = (240 / size as u32).clamp(10, 28);
+    chart.draw_series(cells.iter().map(|&(row, column)| {
+        let coefficient = matrix.coefficients[row][column];
+        let p_value = matrix.p_values[row][column];
+        let stars = match p_value {
+            p if p < 0.001 => "***",
+            p if p < 0.01 => "**",
+            p if p < 0.05 => "*",
+            _ => "",
+        };
+        let label = match (coefficient.is_nan(), row == column) {
+            (true, _) => "n/a".to_string(),
+            (false, true) => format!("{:.2}", coefficient),
+            (false, false) => format!("{:.2}{}", coefficient, stars),
+        };
+
+        // Light text keeps the label readable on strongly colored cells
+        let color = if coefficient.abs() > 0.6 {
+            RGBColor(255, 255, 255)
+        } else {
+            RGBColor(0, 0, 0)
+        };
+        let style = options
+            .font(font_size)
+            .color(&color)
+            .pos(Pos::new(HPos::Center, VPos::Center));
+        Text::new(
+            label,
+            (
+                SegmentValue::CenterOf(column),
+                SegmentValue::CenterOf(size - 1 - row),
+            ),
+            style,
+        )
+    }))?;
+
+    Ok(())
+}
+
+// The theme's negative color for -1 through white for 0 to its positive color
+// for +1, grey when undefined
+fn diverging_color(coefficient: f64, theme: &Theme) -> RGBColor {
+    if coefficient.is_nan() {
+        return RGBColor(200, 200, 200);
+    }
+
+    let strength = coefficient.abs().min(1.0);
+    let RGBColor(r, g, b) = if coefficient >= 0.0 {
+        theme.positive
+    } else {
+        theme.negative
+    };
+    let blend = |channel: u8| (255.0 + (channel as f64 - 255.0) * strength).round() as u8;
+    RGBColor(blend(r), blend(g), blend(b))
+}
+
+/// Draws the share of variance each principal component explains as bars,
+/// with the cumulative share as a line.
+pub fn create_scree_plot(pca: &Pca, options: &ChartOptions) -> Result<String, PipelineError> {
+    let output_path = options.path(None, "pca_scree_plot");
+    render!(options, &output_path, options.size, |root| {
+        draw_scree_plot(root, pca, options)
+    });
+    Ok(output_path)
+}
+
+fn draw_scree_plot<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    pca: &Pca,
+    options: &ChartOptions,
+) -> Result<(), PipelineError> {
+    let components = pca.explained_variance_ratio.len();
+
+    // Integer ranges include their end, so this is one segment per component
+    let mut chart = chart_builder(root, "PCA Scree Plot", options).build_cartesian_2d(
+        (0..components.saturating_sub(1)).into_segmented(),
+        0.0..105.0,
+    )?;
+
+    themed_mesh!(chart, options)
+        .disable_x_mesh()
+        .x_desc("Component")
+        .y_desc("Explained variance (%)")
+        .x_label_formatter(&|x| match x {
+            SegmentValue::CenterOf(index) if *index < components => format!("PC{}", index + 1),
+            _ => "".to_string(),
+        })
+        .draw()?;
+
     chart
-        .configure_series_labels()
-        .border_style(&BLACK)
-        .draw()
-        .unwrap();
+        .draw_series(
+            Histogram::vertical(&chart)
+                .style(options.theme.primary.filled())
+                .margin(options.px(20))
+                .data(
+                    pca.explained_variance_ratio
+                        .iter()
+                        .enumerate()
+                        .map(|(index, ratio)| (index, ratio * 100.0)),
+                ),
+        )?
+        .label("Component")
+        .legend(options.legend_box(options.theme.primary.filled()));
+
+    let cumulative: Vec<(SegmentValue<usize>, f64)> = pca
+        .explained_variance_ratio
+        .iter()
+        .scan(0.0, |total, ratio| {
+            *total += ratio * 100.0;
+            Some(*total)
+        })
+        .enumerate()
+        .map(|(index, total)| (SegmentValue::CenterOf(index), total))
+        .collect();
+
+    let line = options.theme.secondary.stroke_width(options.px(2));
+    chart
+        .draw_series(LineSeries::new(cumulative.clone(), line))?
+        .label("Cumulative")
+        .legend(options.legend_line(line));
+    chart.draw_series(
+        cumulative
+            .into_iter()
+            .map(|point| Circle::new(point, options.px(4), options.theme.secondary.filled())),
+    )?;
+
+    draw_legend(&mut chart, options, SeriesLabelPosition::MiddleRight)
+}
+
+/// Draws the rows projected onto the first two principal components.
+pub fn create_pca_scatter(pca: &Pca, options: &ChartOptions) -> Result<String, PipelineError> {
+    let output_path = options.path(None, "pca_scatter");
+
+    let scores = pca
+        .scores
+        .as_ref()
+        .filter(|scores| scores.width() >= 2)
+        .ok_or_else(|| {
+            PipelineError::EmptyData(
+                "the PC scatter needs rows projected onto two components".to_string(),
+            )
+        })?;
+    let pc1: Vec<f64> = scores.column("PC1")?.f64()?.into_no_null_iter().collect();
+    let pc2: Vec<f64> = scores.column("PC2")?.f64()?.into_no_null_iter().collect();
+
+    render!(options, &output_path, options.size, |root| {
+        draw_pca_scatter(root, pca, &pc1, &pc2, options)
+    });
+    Ok(output_path)
+}
+
+fn draw_pca_scatter<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    pca: &Pca,
+    pc1: &[f64],
+    pc2: &[f64],
+    options: &ChartOptions,
+) -> Result<(), PipelineError> {
+    // Beyond this many points the SVG grows without showing more structure
+    const MAX_POINTS: usize = 10_000;
+
+    let range = |values: &[f64]| {
+        let low = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let high = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let padding = if high > low { (high - low) * 0.05 } else { 0.5 };
+        (low - padding)..(high + padding)
+    };
+
+    let mut chart = chart_builder(root, "PCA Projection", options)
+        .build_cartesian_2d(range(pc1), range(pc2))?;
+
+    themed_mesh!(chart, options)
+        .x_desc(format!(
+            "PC1 ({:.1}%)",
+            pca.explained_variance_ratio[0] * 100.0
+        ))
+        .y_desc(format!(
+            "PC2 ({:.1}%)",
+            pca.explained_variance_ratio[1] * 100.0
+        ))
+        .draw()?;
+
+    // Thin large inputs evenly rather than drawing every row
+    let step = pc1.len().div_ceil(MAX_POINTS).max(1);
+    chart.draw_series(pc1.iter().zip(pc2).step_by(step).map(|(x, y)| {
+        Circle::new(
+            (*x, *y),
+            options.px(2),
+            options.theme.primary.mix(0.5).filled(),
+        )
+    }))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn themes_without_captions_leave_every_caption_out() {
+        let summary = DataSummary {
+            column: "value".to_string(),
+            group: None,
+            count: 4,
+            null_count: 0,
+            min: 1.0,
+            max: 4.0,
+            mean: 2.5,
+            median: 2.5,
+            std_dev: 1.29,
+            variance: 1.67,
+            q1: 1.75,
+            q3: 3.25,
+            iqr: 1.5,
+            skewness: 0.0,
+            kurtosis: -1.2,
+        };
+        let output_dir = std::env::temp_dir().join(format!("captions-{}", std::process::id()));
+        fs::create_dir_all(&output_dir).unwrap();
+        let svg = |captions: bool| {
+            let mut options = ChartOptions::new(
+                "light",
+                ChartFormat::Svg,
+                None,
+                96,
+                output_dir.to_str().unwrap(),
+                "{column}_{chart}",
+            )
+            .unwrap();
+            options.theme.captions = captions;
+            create_summary_chart(&summary, &options).unwrap();
+            fs::read_to_string(options.summary_chart_path(&summary)).unwrap()
+        };
+
+        let with_captions = svg(true);
+        let without_captions = svg(false);
+        fs::remove_dir_all(&output_dir).unwrap();
 
-    println!("Chart saved to {}", output_path);
-}
\ No newline at end of file
+        assert!(with_captions.contains("Statistical Summary"));
+        assert!(with_captions.contains("Mean: "));
+        assert!(!without_captions.contains("Statistical Summary"));
+        for name in ["Mean: ", "Std dev: ", "Variance: "] {
+            assert!(!without_captions.contains(name), "{:?} was drawn", name);
+        }
+    }
+}